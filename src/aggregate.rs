@@ -0,0 +1,193 @@
+use crate::{Month, Time, TimeEntry, Week};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::collections::{BTreeMap, HashMap};
+
+impl Time {
+    /// Tracked time per week. Weeks start on `week_start`,
+    /// Ex. `Weekday::Mon` for ISO weeks
+    #[must_use]
+    pub fn totals_by_week(&self, week_start: Weekday) -> BTreeMap<Week, Duration> {
+        self.totals_by(|date| Week::starting_on(date, week_start))
+    }
+
+    /// Tracked time per calendar month
+    #[must_use]
+    pub fn totals_by_month(&self) -> BTreeMap<Month, Duration> {
+        self.totals_by(Month::of)
+    }
+
+    /// Tracked time per day of the week across every tracked day,
+    /// Ex. to compare Mondays with Fridays. Weekdays without any time
+    /// are left out. `Weekday` isn't ordered so use `Weekday::succ` to
+    /// walk the days in calendar order
+    #[must_use]
+    pub fn totals_by_weekday(&self) -> HashMap<Weekday, Duration> {
+        let mut totals = HashMap::new();
+        for date in self.entries.keys() {
+            *totals.entry(date.weekday()).or_insert_with(Duration::zero) += self.total_for(*date);
+        }
+
+        totals
+    }
+
+    /// Tracked time per tag. Entries with several tags count towards each
+    /// of them and entries without tags are summed under None
+    #[must_use]
+    pub fn totals_by_tag(&self) -> BTreeMap<Option<String>, Duration> {
+        self.totals_by_label(TimeEntry::tags)
+    }
+
+    /// Tracked time per project, entries without a project are summed
+    /// under None
+    #[must_use]
+    pub fn totals_by_project(&self) -> BTreeMap<Option<String>, Duration> {
+        self.totals_by_label(TimeEntry::projects)
+    }
+
+    /// Tracked time per description, ignoring case, labels and metadata
+    /// so `Code review #backend` and `code  review` are summed together.
+    /// Keys are the lowercased `TimeEntry::plain_description`
+    #[must_use]
+    pub fn totals_by_description(&self) -> BTreeMap<String, Duration> {
+        let mut totals = BTreeMap::new();
+        for (_, e) in self.iter_entries().filter(|(_, e)| !e.cancelled) {
+            *totals
+                .entry(e.plain_description().to_lowercase())
+                .or_insert_with(Duration::zero) += e.duration();
+        }
+
+        totals
+    }
+
+    fn totals_by_label<F>(&self, labels: F) -> BTreeMap<Option<String>, Duration>
+    where
+        F: Fn(&TimeEntry) -> Vec<&str>,
+    {
+        let mut totals = BTreeMap::new();
+        for (_, e) in self.iter_entries().filter(|(_, e)| !e.cancelled) {
+            let labels = labels(e);
+            if labels.is_empty() {
+                *totals.entry(None).or_insert_with(Duration::zero) += e.duration();
+            }
+            for label in labels {
+                *totals
+                    .entry(Some(label.to_string()))
+                    .or_insert_with(Duration::zero) += e.duration();
+            }
+        }
+
+        totals
+    }
+
+    /// Sums each day's total into the bucket `period` maps it to
+    fn totals_by<K, F>(&self, period: F) -> BTreeMap<K, Duration>
+    where
+        K: Ord,
+        F: Fn(NaiveDate) -> K,
+    {
+        let mut totals = BTreeMap::new();
+        for date in self.entries.keys() {
+            *totals.entry(period(*date)).or_insert_with(Duration::zero) += self.total_for(*date);
+        }
+
+        totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_time, Month, Week};
+    use chrono::{Duration, NaiveDate, Weekday};
+    use std::error::Error;
+
+    #[test]
+    fn test_totals_by_period() -> Result<(), Box<dyn Error>> {
+        // 1822-01-27 is a Sunday
+        let input = "1822-01-26\n\
+        3:00 4:00 Sketched ideas\n\
+        1822-01-27\n\
+        4:00 6:00 Created the first computer\n\
+        1822-02-01\n\
+        15:00 18:00 Decided on the name\n";
+
+        let t = parse_time(input)?;
+        let d = |month, day| NaiveDate::from_ymd_opt(1822, month, day).unwrap();
+
+        let weeks: Vec<_> = t
+            .totals_by_week(Weekday::Mon)
+            .into_iter()
+            .map(|(w, total)| (w.start(), total))
+            .collect();
+        assert_eq!(
+            weeks,
+            vec![
+                (d(1, 21), Duration::hours(3)),
+                (d(1, 28), Duration::hours(3))
+            ]
+        );
+
+        let weeks = t.totals_by_week(Weekday::Sun);
+        assert_eq!(
+            weeks[&Week::starting_on(d(1, 20), Weekday::Sun)],
+            Duration::hours(1)
+        );
+        assert_eq!(
+            weeks[&Week::starting_on(d(1, 27), Weekday::Sun)],
+            Duration::hours(5)
+        );
+
+        let months: Vec<_> = t.totals_by_month().into_iter().collect();
+        assert_eq!(
+            months,
+            vec![
+                (Month::of(d(1, 1)), Duration::hours(3)),
+                (Month::of(d(2, 1)), Duration::hours(3))
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_totals_by_weekday() -> Result<(), Box<dyn Error>> {
+        // 1822-01-14 and 1822-01-21 are Mondays
+        let input = "1822-01-14\n\
+        3:00 4:00 Sketched ideas\n\
+        1822-01-18\n\
+        4:00 6:00 Created the first computer\n\
+        1822-01-21\n\
+        15:00 18:00 Decided on the name\n";
+
+        let weekdays = parse_time(input)?.totals_by_weekday();
+        assert_eq!(weekdays.len(), 2);
+        assert_eq!(weekdays[&Weekday::Mon], Duration::hours(4));
+        assert_eq!(weekdays[&Weekday::Fri], Duration::hours(2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_totals_by_label() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-15\n\
+        3:00 4:00 Sketched ideas #design +engine\n\
+        4:00 6:00 Built the prototype #design #build +engine\n\
+        6:00 7:00 Lunch\n\
+        x 7:00 9:00 Cancelled #build\n";
+
+        let t = parse_time(input)?;
+        let label = |s: &str| Some(s.to_string());
+
+        let tags = t.totals_by_tag();
+        assert_eq!(tags.len(), 3);
+        assert_eq!(tags[&label("design")], Duration::hours(3));
+        assert_eq!(tags[&label("build")], Duration::hours(2));
+        assert_eq!(tags[&None], Duration::hours(1));
+
+        let projects = t.totals_by_project();
+        assert_eq!(projects[&label("engine")], Duration::hours(3));
+        assert_eq!(projects[&None], Duration::hours(1));
+
+        let descriptions = t.totals_by_description();
+        assert_eq!(descriptions.len(), 3);
+        assert_eq!(descriptions["sketched ideas"], Duration::hours(1));
+        Ok(())
+    }
+}
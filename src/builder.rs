@@ -0,0 +1,150 @@
+use crate::edit::check_entry;
+use crate::{ParseError, TimeEntry, OPEN_END};
+use chrono::{NaiveDate, NaiveTime};
+
+/// Anything the builder accepts as a start or end time, either a
+/// `NaiveTime` or a string in the time.txt `H:MM` format
+pub trait TimeInput {
+    /// # Errors
+    /// Errors if the value isn't a valid time
+    fn into_time(self) -> Result<NaiveTime, ParseError>;
+}
+
+impl TimeInput for NaiveTime {
+    fn into_time(self) -> Result<NaiveTime, ParseError> {
+        Ok(self)
+    }
+}
+
+impl TimeInput for &str {
+    fn into_time(self) -> Result<NaiveTime, ParseError> {
+        NaiveTime::parse_from_str(self.trim(), "%H:%M")
+            .map_err(|source| ParseError::BadTime { line: None, source })
+    }
+}
+
+/// Builds a `TimeEntry` without hand parsing times,
+/// Ex. `TimeEntry::builder().date(d).start("09:00")?.end("10:30")?.build()?`
+#[derive(Debug, Clone, Default)]
+pub struct TimeEntryBuilder {
+    date: Option<NaiveDate>,
+    start: Option<NaiveTime>,
+    end: Option<NaiveTime>,
+    description: String,
+    cancelled: bool,
+}
+
+impl TimeEntry {
+    #[must_use]
+    pub fn builder() -> TimeEntryBuilder {
+        TimeEntryBuilder::default()
+    }
+}
+
+impl TimeEntryBuilder {
+    #[must_use]
+    pub fn date(mut self, date: NaiveDate) -> Self {
+        self.date = Some(date);
+        self
+    }
+
+    /// # Errors
+    /// Errors if `start` isn't a valid time
+    pub fn start<T: TimeInput>(mut self, start: T) -> Result<Self, ParseError> {
+        self.start = Some(start.into_time()?);
+        Ok(self)
+    }
+
+    /// Leaving out the end time builds an open entry
+    ///
+    /// # Errors
+    /// Errors if `end` isn't a valid time
+    pub fn end<T: TimeInput>(mut self, end: T) -> Result<Self, ParseError> {
+        self.end = Some(end.into_time()?);
+        Ok(self)
+    }
+
+    /// Same as `end` but also accepts `--:--` to keep the entry open
+    ///
+    /// # Errors
+    /// Errors if `end` is neither a valid time nor `--:--`
+    pub fn end_str(self, end: &str) -> Result<Self, ParseError> {
+        if end.trim() == OPEN_END {
+            Ok(TimeEntryBuilder { end: None, ..self })
+        } else {
+            self.end(end)
+        }
+    }
+
+    #[must_use]
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.trim().to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn cancelled(mut self, cancelled: bool) -> Self {
+        self.cancelled = cancelled;
+        self
+    }
+
+    /// # Errors
+    /// Errors if the date or start time is missing, or the entry is
+    /// invalid in the same ways `Time::add_entry` rejects
+    pub fn build(self) -> Result<TimeEntry, ParseError> {
+        let Some(date) = self.date else {
+            return Err(ParseError::InvalidEntry("entry has no date".to_string()));
+        };
+        let Some(start) = self.start else {
+            return Err(ParseError::MissingStartTime { line: None });
+        };
+
+        let entry = TimeEntry {
+            date,
+            start,
+            end: self.end,
+            description: self.description,
+            cancelled: self.cancelled,
+        };
+        check_entry(&entry)?;
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TimeEntry;
+    use chrono::{Duration, NaiveDate, NaiveTime};
+    use std::error::Error;
+
+    #[test]
+    fn test_builder() -> Result<(), Box<dyn Error>> {
+        let d = NaiveDate::from_ymd_opt(1822, 1, 15).unwrap();
+
+        let e = TimeEntry::builder()
+            .date(d)
+            .start("9:00")?
+            .end(NaiveTime::from_hms_opt(10, 30, 0).unwrap())?
+            .description(" Sprint planning ")
+            .build()?;
+        assert_eq!(format!("{e}"), "09:00 10:30 Sprint planning");
+        assert_eq!(e.duration(), Duration::minutes(90));
+
+        let open = TimeEntry::builder()
+            .date(d)
+            .start("11:00")?
+            .end_str("--:--")?
+            .build()?;
+        assert!(open.is_open());
+
+        assert!(TimeEntry::builder().start("25:00").is_err());
+        assert!(TimeEntry::builder().start("9:00")?.build().is_err());
+        assert!(TimeEntry::builder()
+            .date(d)
+            .start("9:00")?
+            .end("9:00")?
+            .build()
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use super::{now_for, read_for_edit, write_file};
+use chrono::NaiveDate;
+use clap::Args;
+use std::error::Error;
+use std::path::Path;
+use timetxt::TimeEntry;
+
+#[derive(Debug, Args)]
+pub struct AddArgs {
+    /// Start time, Ex. 09:00
+    start: String,
+    /// End time, Ex. 10:30, or --:-- to leave the entry open
+    #[arg(allow_hyphen_values = true)]
+    end: String,
+    /// What the time was spent on
+    #[arg(required = true, num_args = 1..)]
+    description: Vec<String>,
+    /// Date to add the entry to as YYYY-MM-DD [default: today]
+    #[arg(short, long)]
+    date: Option<NaiveDate>,
+}
+
+pub fn run(file: &Path, args: AddArgs) -> Result<(), Box<dyn Error>> {
+    let contents = read_for_edit(file)?;
+    let date = match args.date {
+        Some(date) => date,
+        None => now_for(&contents)?.date(),
+    };
+    let entry = TimeEntry::builder()
+        .date(date)
+        .start(args.start.as_str())?
+        .end_str(&args.end)?
+        .description(&args.description.join(" "))
+        .build()?;
+    write_file(file, &timetxt::insert_entry(&contents, &entry))?;
+    println!("{entry}");
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use super::{read_for_edit, write_file, UsageError};
+use chrono::NaiveTime;
+use clap::Args;
+use std::error::Error;
+use std::path::Path;
+use timetxt::TimeEntry;
+
+#[derive(Debug, Args)]
+pub struct AmendArgs {
+    /// A new description [default: the one it has]
+    description: Vec<String>,
+    /// A new start time, Ex. 09:00
+    #[arg(long, value_parser = parse_clock)]
+    start: Option<NaiveTime>,
+    /// A new end time, Ex. 10:30
+    #[arg(long, value_parser = parse_clock)]
+    end: Option<NaiveTime>,
+    /// Add a tag, without the #. Can be repeated
+    #[arg(long, value_name = "TAG")]
+    tag: Vec<String>,
+    /// Remove a tag, without the #. Can be repeated
+    #[arg(long, value_name = "TAG")]
+    untag: Vec<String>,
+}
+
+/// Changes the latest entry, the one with the latest date and start time,
+/// and prints it as it now reads
+pub fn run(file: &Path, args: &AmendArgs) -> Result<(), Box<dyn Error>> {
+    if args.description.is_empty()
+        && args.start.is_none()
+        && args.end.is_none()
+        && args.tag.is_empty()
+        && args.untag.is_empty()
+    {
+        let message = "nothing to amend, pass a description, --start, --end, --tag or --untag";
+        return Err(UsageError(message.to_string()).into());
+    }
+
+    let contents = read_for_edit(file)?;
+    let (contents, entry) = timetxt::amend_latest_entry(&contents, |e| amend(e, args))?
+        .ok_or("there is no entry to amend")?;
+    write_file(file, &contents)?;
+    println!("Amended {entry}");
+    Ok(())
+}
+
+fn amend(e: TimeEntry, args: &AmendArgs) -> TimeEntry {
+    let description = if args.description.is_empty() {
+        e.description.clone()
+    } else {
+        args.description.join(" ")
+    };
+
+    let mut words: Vec<String> = description
+        .split_whitespace()
+        .filter(|w| {
+            !args
+                .untag
+                .iter()
+                .any(|t| w.strip_prefix('#') == Some(t.as_str()))
+        })
+        .map(str::to_string)
+        .collect();
+    // Tags can follow the metadata without hiding it
+    for tag in args.tag.iter().map(|t| format!("#{t}")) {
+        if !words.contains(&tag) {
+            words.push(tag);
+        }
+    }
+
+    TimeEntry {
+        start: args.start.unwrap_or(e.start),
+        end: args.end.or(e.end),
+        description: words.join(" "),
+        ..e
+    }
+}
+
+fn parse_clock(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s, "%H:%M").map_err(|_| format!("{s:?} isn't a time like 09:30"))
+}
@@ -0,0 +1,71 @@
+use super::{read_for_edit, write_file, FileError};
+use chrono::{Datelike, NaiveDate};
+use clap::Args;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Args)]
+pub struct ArchiveArgs {
+    /// Move the days before this date, as YYYY-MM-DD
+    #[arg(long, value_name = "DATE")]
+    before: NaiveDate,
+}
+
+/// Moves the days before the cutoff into one archive file per year next
+/// to the file, Ex. `time-archive-2023.txt`. Days are added to the end of
+/// archives that already exist
+pub fn run(file: &Path, args: &ArchiveArgs) -> Result<(), Box<dyn Error>> {
+    let contents = read_for_edit(file)?;
+    // Only whole days are moved, a file that doesn't parse could lose lines
+    let t = timetxt::parse_time(&contents).map_err(|e| FileError::new(file, e))?;
+    let years: BTreeSet<i32> = t
+        .days()
+        .filter(|d| *d < args.before)
+        .map(|d| d.year())
+        .collect();
+    if years.is_empty() {
+        println!("No days before {}", args.before);
+        return Ok(());
+    }
+
+    let (kept, old) = timetxt::take_days(&contents, |d| d < args.before);
+    // The archives are written first so a failure can't lose any days
+    for year in years {
+        let archive = archive_path(file, year);
+        let (_, days) = timetxt::take_days(&old, |d| d.year() == year);
+        append_days(&archive, &days)?;
+
+        let count = t
+            .days()
+            .filter(|d| *d < args.before && d.year() == year)
+            .count();
+        let days = if count == 1 { "day" } else { "days" };
+        println!("Moved {count} {days} to {}", archive.display());
+    }
+    write_file(file, &kept)?;
+    Ok(())
+}
+
+/// Adds days taken out of another file to the end of `path`, a blank line
+/// after what's already there
+pub fn append_days(path: &Path, days: &str) -> Result<(), Box<dyn Error>> {
+    let mut contents = read_for_edit(path)?;
+    if !contents.trim().is_empty() {
+        if !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        contents.push('\n');
+    }
+    contents.push_str(days);
+    write_file(path, &contents)?;
+    Ok(())
+}
+
+/// `time.txt` is archived to `time-archive-2023.txt` in the same directory
+fn archive_path(file: &Path, year: i32) -> PathBuf {
+    let stem = file
+        .file_stem()
+        .map_or_else(|| "time".into(), |s| s.to_string_lossy());
+    file.with_file_name(format!("{stem}-archive-{year}.txt"))
+}
@@ -0,0 +1,101 @@
+use super::table::{Cell, Table};
+use super::{Global, Inputs, RoundArgs};
+use chrono::{Duration, NaiveDate};
+use clap::{Args, ValueEnum};
+use std::collections::BTreeMap;
+use std::error::Error;
+use timetxt::TimeEntry;
+
+#[derive(Debug, Args)]
+pub struct BillableArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// What to split the totals by
+    #[arg(long, value_enum, default_value_t = By::Day)]
+    by: By,
+    #[command(flatten)]
+    round: RoundArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum By {
+    Day,
+    Project,
+}
+
+/// Billable and non-billable time per day or project. Entries are
+/// billable with `billable:yes` metadata or a project listed in the
+/// configured billable_projects, `billable:no` wins over the list
+pub fn run(global: &Global, args: &BillableArgs) -> Result<(), Box<dyn Error>> {
+    let t = global.read(&args.inputs)?;
+
+    // Days sort by date whatever the date format, projects by name with
+    // entries without one last
+    let mut rows: BTreeMap<(Option<NaiveDate>, bool, String), (Duration, Duration)> =
+        BTreeMap::new();
+    for (date, e) in t.iter_entries().filter(|(_, e)| !e.cancelled) {
+        let key = match args.by {
+            By::Day => (Some(*date), false, global.date(*date)),
+            By::Project => match e.projects().first() {
+                Some(project) => (None, false, format!("+{project}")),
+                None => (None, true, "(none)".to_string()),
+            },
+        };
+        let (billable, other) = rows
+            .entry(key)
+            .or_insert((Duration::zero(), Duration::zero()));
+        if is_billable(global, e) {
+            *billable += e.duration();
+        } else {
+            *other += e.duration();
+        }
+    }
+
+    let group = match args.by {
+        By::Day => "Date",
+        By::Project => "Project",
+    };
+    let mut table = Table::new([group, "Billable", "Non-billable", "Total", "Billable share"]);
+    let row = |label: Cell, billable: Duration, other: Duration| {
+        let total = billable + other;
+        let share = if total > Duration::zero() {
+            (billable.num_minutes() * 100 + total.num_minutes() / 2) / total.num_minutes()
+        } else {
+            0
+        };
+        vec![
+            label,
+            Cell::Duration(billable),
+            Cell::Duration(other),
+            Cell::Duration(total),
+            Cell::Percent(share),
+        ]
+    };
+    let (mut billable, mut other) = (Duration::zero(), Duration::zero());
+    for ((_, _, label), (b, o)) in rows {
+        let (b, o) = (args.round.apply(global, b), args.round.apply(global, o));
+        billable += b;
+        other += o;
+        table.push(row(Cell::Text(label), b, o));
+    }
+    table.footer(row(Cell::text("Total"), billable, other));
+    table.print(global.output)
+}
+
+fn is_billable(global: &Global, e: &TimeEntry) -> bool {
+    match e
+        .meta()
+        .get("billable")
+        .map(|b| b.to_lowercase())
+        .as_deref()
+    {
+        Some("yes" | "true") => true,
+        Some("no" | "false") => false,
+        _ => e.projects().iter().any(|p| {
+            global
+                .billable_projects
+                .iter()
+                .any(|b| b.eq_ignore_ascii_case(p))
+        }),
+    }
+}
@@ -0,0 +1,15 @@
+use super::{read_for_edit, write_file};
+use std::error::Error;
+use std::path::Path;
+
+/// Removes the running entry from the file without recording any time
+pub fn run(file: &Path) -> Result<(), Box<dyn Error>> {
+    let contents = read_for_edit(file)?;
+    let Some((contents, entry)) = timetxt::remove_open_entry(&contents) else {
+        return Err("no entry is running".into());
+    };
+
+    write_file(file, &contents)?;
+    println!("Cancelled {entry}");
+    Ok(())
+}
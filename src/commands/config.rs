@@ -0,0 +1,323 @@
+//! Defaults read from `~/.config/timetxt/config.toml`, Ex.
+//!
+//! ```toml
+//! file = "~/notes/time.txt"
+//! week_start = "sunday"
+//! fiscal_year_start = "april"
+//! holidays = ["12-25", "12-26", "2024-05-09"]
+//! round = "15m"
+//! round_mode = "up"
+//! expected_hours = 8
+//! hourly_rate = 95.0
+//! date_format = "%d.%m.%Y"
+//! goal = "40h/week"
+//! billable_projects = ["acme", "initech"]
+//! templates = ["standup 09:30 09:45 every weekday"]
+//!
+//! [clients]
+//! "ACME Corp" = ["acme-api", "acme-web"]
+//!
+//! [workspaces]
+//! work = "~/time/work.txt"
+//! oss = "~/time/oss.txt"
+//!
+//! [invoice]
+//! currency = "€"
+//! currency_after = true
+//! decimal_separator = ","
+//! thousands_separator = "."
+//! tax = 19
+//! tax_name = "VAT"
+//! number = "INV-%Y-%m"
+//! payee = """
+//! Jane Doe
+//! 1 Main St, Springfield
+//! """
+//!
+//! [invoice.payers]
+//! "ACME Corp" = """
+//! ACME Corp
+//! 2 Market St, Springfield
+//! """
+//! ```
+//!
+//! Flags given on the command line win over the file. Holidays are listed
+//! by date, a country code would need a calendar of every country's
+//! holidays and isn't supported
+
+use super::fill::Template;
+use super::goal::Goal;
+use super::table::Currency;
+use super::{expand_home, FileError, RoundMode, UsageError};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use timetxt::Rounding;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The file used when none is given
+    pub file: Option<PathBuf>,
+    #[serde(deserialize_with = "weekday")]
+    pub week_start: Option<Weekday>,
+    /// The month fiscal years start in, for `report --quarterly` and
+    /// `--yearly`
+    #[serde(deserialize_with = "month")]
+    pub fiscal_year_start: Option<u32>,
+    /// Days without expected hours, see `Holiday`
+    #[serde(deserialize_with = "holidays")]
+    pub holidays: Vec<Holiday>,
+    #[serde(deserialize_with = "duration")]
+    pub round: Option<Duration>,
+    pub round_mode: RoundMode,
+    /// The default `report --target`
+    #[serde(deserialize_with = "duration")]
+    pub expected_hours: Option<Duration>,
+    pub hourly_rate: Option<f64>,
+    /// A strftime format for printed dates, Ex. `%d.%m.%Y`
+    #[serde(deserialize_with = "date_format")]
+    pub date_format: Option<String>,
+    /// Hours to track a day or week, Ex. `40h/week`
+    #[serde(deserialize_with = "goal")]
+    pub goal: Option<Goal>,
+    /// Projects without the `+`, see `timetxt billable`
+    pub billable_projects: Vec<String>,
+    /// Recurring entries, see `timetxt fill`
+    #[serde(deserialize_with = "templates")]
+    pub templates: Vec<Template>,
+    /// The projects of each client, without the `+`
+    pub clients: BTreeMap<String, Vec<String>>,
+    /// Files by name for `--workspace`
+    pub workspaces: BTreeMap<String, PathBuf>,
+    pub invoice: InvoiceConfig,
+}
+
+/// A public holiday, `YYYY-MM-DD` for one day or `MM-DD` for the same day
+/// every year
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Holiday {
+    Date(NaiveDate),
+    Yearly { month: u32, day: u32 },
+}
+
+impl Holiday {
+    pub fn is_on(&self, date: NaiveDate) -> bool {
+        match *self {
+            Holiday::Date(d) => d == date,
+            Holiday::Yearly { month, day } => date.month() == month && date.day() == day,
+        }
+    }
+}
+
+impl FromStr for Holiday {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Holiday, String> {
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Ok(Holiday::Date(date));
+        }
+        // A leap year so 02-29 is a valid yearly holiday
+        NaiveDate::parse_from_str(&format!("2000-{s}"), "%Y-%m-%d")
+            .map(|d| Holiday::Yearly {
+                month: d.month(),
+                day: d.day(),
+            })
+            .map_err(|_| format!("{s:?} isn't a holiday like 2024-12-25 or 12-25"))
+    }
+}
+
+/// The `[invoice]` table, see `timetxt invoice`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InvoiceConfig {
+    /// Printed before amounts, or after them with `currency_after`
+    pub currency: String,
+    pub currency_after: bool,
+    pub decimal_separator: Option<String>,
+    pub thousands_separator: String,
+    /// A percentage added to the total
+    pub tax: Option<f64>,
+    /// What the tax is called on the invoice [default: Tax]
+    pub tax_name: Option<String>,
+    /// A strftime format for the invoice number, filled in with the
+    /// invoice date, Ex. `INV-%Y-%m`
+    #[serde(deserialize_with = "date_format")]
+    pub number: Option<String>,
+    /// Who is paid, printed above the entries
+    pub payee: Option<String>,
+    /// The address block of each client, printed when invoicing one
+    pub payers: BTreeMap<String, String>,
+}
+
+impl InvoiceConfig {
+    pub fn currency(&self) -> Currency {
+        let mut currency = Currency {
+            symbol: self.currency.clone(),
+            symbol_after: self.currency_after,
+            thousands_separator: self.thousands_separator.clone(),
+            ..Currency::default()
+        };
+        if let Some(separator) = &self.decimal_separator {
+            currency.decimal_separator = separator.clone();
+        }
+        currency
+    }
+}
+
+impl Config {
+    /// Reads `path`, or the default location without one. Only a missing
+    /// default file is fine, it gives the default config
+    pub fn load(path: Option<&Path>) -> Result<Config, Box<dyn Error>> {
+        let (path, required) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Config::default()),
+            },
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !required => {
+                return Ok(Config::default());
+            }
+            result => result.map_err(|e| FileError::new(&path, e))?,
+        };
+        let mut config: Config = toml::from_str(&contents).map_err(|e| FileError::new(&path, e))?;
+        config.file = config.file.map(|f| expand_home(&f));
+        for file in config.workspaces.values_mut() {
+            *file = expand_home(file);
+        }
+        Ok(config)
+    }
+
+    /// The files of the named workspaces, `all` is every one of them
+    pub fn workspace_files(&self, names: &[String]) -> Result<Vec<PathBuf>, UsageError> {
+        let mut files = Vec::new();
+        for name in names {
+            if name == "all" {
+                files.extend(self.workspaces.values().cloned());
+                continue;
+            }
+            let file = self.workspaces.get(name).ok_or_else(|| {
+                let known: Vec<&str> = self.workspaces.keys().map(String::as_str).collect();
+                UsageError(if known.is_empty() {
+                    format!("no workspace {name:?}, the config has no [workspaces]")
+                } else {
+                    format!(
+                        "no workspace {name:?}, expected one of {}",
+                        known.join(", ")
+                    )
+                })
+            })?;
+            files.push(file.clone());
+        }
+        Ok(files)
+    }
+
+    pub fn rounding(&self) -> Option<Rounding> {
+        self.round.map(|step| self.round_mode.rounding(step))
+    }
+}
+
+/// `$XDG_CONFIG_HOME/timetxt/config.toml`, falling back to `~/.config`
+fn default_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(dir.join("timetxt").join("config.toml"))
+}
+
+fn weekday<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Weekday>, D::Error> {
+    let day = String::deserialize(d)?;
+    day.parse()
+        .map(Some)
+        .map_err(|_| de::Error::custom(format!("{day:?} isn't a day of the week")))
+}
+
+fn month<'de, D: Deserializer<'de>>(d: D) -> Result<Option<u32>, D::Error> {
+    let month = String::deserialize(d)?;
+    month
+        .parse::<chrono::Month>()
+        .map(|m| Some(m.number_from_month()))
+        .map_err(|_| de::Error::custom(format!("{month:?} isn't a month")))
+}
+
+/// A duration like `7h30m` or `7:30`, or a number of hours
+fn duration<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Duration>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Hours {
+        Number(f64),
+        Text(String),
+    }
+
+    let text = match Hours::deserialize(d)? {
+        Hours::Number(hours) => hours.to_string(),
+        Hours::Text(text) => text,
+    };
+    timetxt::parse_duration(&text)
+        .map(Some)
+        .map_err(de::Error::custom)
+}
+
+/// Rejects formats that can't print a date, like ones with times, instead
+/// of failing when printing
+fn date_format<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+    let format = String::deserialize(d)?;
+    let mut sample = String::new();
+    if write!(sample, "{}", NaiveDate::MIN.format(&format)).is_err() {
+        return Err(de::Error::custom(format!("invalid date format {format:?}")));
+    }
+    Ok(Some(format))
+}
+
+fn goal<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Goal>, D::Error> {
+    let goal = String::deserialize(d)?;
+    goal.parse().map(Some).map_err(de::Error::custom)
+}
+
+fn templates<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Template>, D::Error> {
+    Vec::<String>::deserialize(d)?
+        .iter()
+        .map(|t| t.parse().map_err(de::Error::custom))
+        .collect()
+}
+
+/// A list of `Holiday`s, naming a country gets a message saying so
+fn holidays<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Holiday>, D::Error> {
+    struct Holidays;
+
+    impl<'de> de::Visitor<'de> for Holidays {
+        type Value = Vec<Holiday>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a list of dates like 2024-12-25 or 12-25")
+        }
+
+        fn visit_str<E: de::Error>(self, country: &str) -> Result<Vec<Holiday>, E> {
+            Err(E::custom(format!(
+                "holidays can't be a country like {country:?}, list the dates, Ex. [\"12-25\", \"2024-05-09\"]"
+            )))
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<Holiday>, A::Error> {
+            let mut holidays = Vec::new();
+            while let Some(holiday) = seq.next_element::<String>()? {
+                holidays.push(holiday.parse().map_err(de::Error::custom)?);
+            }
+            Ok(holidays)
+        }
+    }
+
+    d.deserialize_any(Holidays)
+}
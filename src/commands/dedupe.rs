@@ -0,0 +1,37 @@
+use super::{read_for_edit, write_file};
+use clap::Args;
+use std::error::Error;
+use std::path::Path;
+
+#[derive(Debug, Args)]
+pub struct DedupeArgs {
+    /// Also remove entries with the same times whose description only
+    /// differs in case, spacing, punctuation or word order
+    #[arg(long)]
+    near: bool,
+    /// List the duplicates without changing the file
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Removes entries repeating an earlier one of their day, listing each.
+/// The first of every set of copies is kept
+pub fn run(file: &Path, args: &DedupeArgs) -> Result<(), Box<dyn Error>> {
+    let contents = read_for_edit(file)?;
+    let (deduped, removed) = timetxt::remove_duplicate_entries(&contents, args.near);
+    for e in &removed {
+        println!("duplicate: {} {e}", e.date);
+    }
+
+    let count = removed.len();
+    let s = if count == 1 { "" } else { "s" };
+    if args.dry_run {
+        println!("Found {count} duplicate{s}");
+    } else {
+        if count > 0 {
+            write_file(file, &deduped)?;
+        }
+        println!("Removed {count} duplicate{s}");
+    }
+    Ok(())
+}
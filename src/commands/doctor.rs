@@ -0,0 +1,321 @@
+//! `timetxt doctor`, checking files for the problems that most often get
+//! in the way and saying how to fix them
+
+use super::table::{paint, Style};
+use super::{display_name, is_stdin, read_contents, Global, Inputs};
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+use timetxt::Severity;
+
+/// Problems shown of each kind before the rest are counted
+const SHOWN: usize = 5;
+
+/// Prints every check of each file, with a fix under each problem. Exits
+/// with status 1 if any file has errors
+pub fn run(global: &Global, inputs: &Inputs) -> Result<(), Box<dyn Error>> {
+    let color = global.output.color;
+    let mut errors = 0;
+    let mut warnings = 0;
+    for file in inputs.resolve(global)? {
+        println!("{}", paint(&display_name(&file), Style::Bold, color));
+        for check in checks(global, &file) {
+            let (label, style) = match check.problem {
+                None => ("ok", Style::Good),
+                Some(Severity::Warning) => ("warning", Style::Note),
+                Some(Severity::Error) => ("error", Style::Bad),
+            };
+            println!(
+                "  {} {}",
+                paint(&format!("{label:<7}"), style, color),
+                check.message
+            );
+            if let Some(fix) = &check.fix {
+                println!("          fix: {fix}");
+            }
+            match check.problem {
+                Some(Severity::Error) => errors += 1,
+                Some(Severity::Warning) => warnings += 1,
+                None => {}
+            }
+        }
+    }
+
+    match (errors, warnings) {
+        (0, 0) => println!("\nNo problems found"),
+        _ => println!(
+            "\n{errors} {}, {warnings} {}",
+            if errors == 1 { "error" } else { "errors" },
+            if warnings == 1 { "warning" } else { "warnings" }
+        ),
+    }
+    if errors > 0 {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+struct Check {
+    /// None for a check that passed
+    problem: Option<Severity>,
+    message: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok<S: Into<String>>(message: S) -> Check {
+        Check {
+            problem: None,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn problem<S: Into<String>, F: Into<String>>(severity: Severity, message: S, fix: F) -> Check {
+        Check {
+            problem: Some(severity),
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+fn checks(global: &Global, file: &Path) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let name = display_name(file);
+
+    // Standard input can only be checked for its contents
+    let bytes = if is_stdin(file) {
+        match read_contents(file) {
+            Ok(contents) => contents.into_bytes(),
+            Err(e) => {
+                checks.push(Check::problem(
+                    Severity::Error,
+                    e.to_string(),
+                    "pass the contents as UTF-8 text",
+                ));
+                return checks;
+            }
+        }
+    } else {
+        match fs::read(file) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                checks.push(Check::problem(
+                    Severity::Error,
+                    "the file doesn't exist",
+                    "create it with `timetxt start`, or point --file or the config's file at yours",
+                ));
+                return checks;
+            }
+            Err(e) => {
+                checks.push(Check::problem(
+                    Severity::Error,
+                    format!("the file can't be read: {e}"),
+                    format!("check its permissions, Ex. chmod u+rw {name}"),
+                ));
+                return checks;
+            }
+        }
+    };
+    if !is_stdin(file) {
+        checks.extend(permissions(file));
+    }
+
+    let contents = match String::from_utf8(bytes) {
+        Ok(contents) => {
+            checks.push(Check::ok("the file is UTF-8"));
+            contents
+        }
+        Err(e) => {
+            let bytes = e.as_bytes();
+            let line = bytes[..e.utf8_error().valid_up_to()]
+                .iter()
+                .filter(|b| **b == b'\n')
+                .count()
+                + 1;
+            checks.push(Check::problem(
+                Severity::Error,
+                format!("line {line} isn't valid UTF-8"),
+                format!("convert the file, Ex. iconv -f latin1 -t utf-8 {name}"),
+            ));
+            String::from_utf8_lossy(bytes).into_owned()
+        }
+    };
+    if contents.starts_with('\u{feff}') {
+        checks.push(Check::problem(
+            Severity::Error,
+            "the file starts with a byte order mark, so its first date isn't read",
+            "save it as UTF-8 without a BOM",
+        ));
+    }
+    if contents.contains("\r\n") {
+        checks.push(Check::problem(
+            Severity::Warning,
+            "the file has Windows line endings, commands that rewrite the whole file like fmt drop them",
+            format!("convert it, Ex. dos2unix {name}"),
+        ));
+    }
+
+    let invalid: Vec<_> = timetxt::validate(&contents)
+        .into_iter()
+        .filter(|d| d.severity == Severity::Error)
+        .collect();
+    if invalid.is_empty() {
+        checks.push(Check::ok("every line parses"));
+    }
+    for d in invalid.iter().take(SHOWN) {
+        checks.push(Check::problem(
+            Severity::Error,
+            format!("line {}: {}", d.line, d.message),
+            "correct the line or comment it out with //",
+        ));
+    }
+    if invalid.len() > SHOWN {
+        checks.push(Check::problem(
+            Severity::Error,
+            format!("{} more lines don't parse", invalid.len() - SHOWN),
+            format!("`timetxt validate {name}` lists every one"),
+        ));
+    }
+
+    // The rest needs the entries, which a broken line keeps from parsing
+    let Ok(t) = timetxt::parse_time(&contents) else {
+        return checks;
+    };
+    let overlaps = t.overlaps();
+    if overlaps.is_empty() {
+        checks.push(Check::ok("no entries overlap"));
+    }
+    for o in overlaps.iter().take(SHOWN) {
+        checks.push(Check::problem(
+            Severity::Warning,
+            format!("{} {} overlaps {}", global.date(o.date), o.first, o.second),
+            "change the times so the overlap isn't counted twice",
+        ));
+    }
+    if overlaps.len() > SHOWN {
+        checks.push(Check::problem(
+            Severity::Warning,
+            format!("{} more overlaps", overlaps.len() - SHOWN),
+            format!("`timetxt overlaps {name}` lists every one"),
+        ));
+    }
+
+    let today = global.now().date();
+    let future: Vec<_> = t.days().filter(|d| *d > today).collect();
+    if future.is_empty() {
+        checks.push(Check::ok("no dates in the future"));
+    }
+    for date in future.iter().take(SHOWN) {
+        checks.push(Check::problem(
+            Severity::Warning,
+            format!("{} is in the future", global.date(*date)),
+            "check the date's year and month",
+        ));
+    }
+    if future.len() > SHOWN {
+        checks.push(Check::problem(
+            Severity::Warning,
+            format!("{} more dates in the future", future.len() - SHOWN),
+            "check the dates' years and months",
+        ));
+    }
+
+    for (date, e) in t.iter_entries() {
+        if e.is_open() && !e.cancelled && *date < today {
+            checks.push(Check::problem(
+                Severity::Warning,
+                format!("{} {e} is still running", global.date(*date)),
+                "give it an end time, or cancel it with an x in front",
+            ));
+        }
+    }
+    checks
+}
+
+/// Edits replace the file with a new one written next to it, so its
+/// directory has to be writable too
+fn permissions(file: &Path) -> Vec<Check> {
+    let dir = match file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut checks = Vec::new();
+    if !writable(file) {
+        checks.push(Check::problem(
+            Severity::Warning,
+            "the file isn't writable, commands that edit it will fail",
+            format!("chmod u+w {}", file.display()),
+        ));
+    } else if !writable(dir) {
+        checks.push(Check::problem(
+            Severity::Warning,
+            format!(
+                "{} isn't writable, commands that edit the file will fail",
+                dir.display()
+            ),
+            format!("chmod u+w {}", dir.display()),
+        ));
+    } else {
+        checks.push(Check::ok("the file can be read and edited"));
+    }
+    checks
+}
+
+#[cfg(unix)]
+fn writable(path: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    match CString::new(path.as_os_str().as_bytes()) {
+        // Safe as the string is NUL terminated and outlives the call
+        Ok(path) => unsafe { libc::access(path.as_ptr(), libc::W_OK) == 0 },
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn writable(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|m| !m.permissions().readonly())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checks_mangled_file() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!("timetxt-doctor-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let file = dir.join("time.txt");
+        fs::write(
+            &file,
+            b"1822-01-15\r\n\xc3\xa9\xc3\xa9\xc3\xa9 10:00 Accents\r\n3:00 4:00 Caf\xe9\r\n",
+        )?;
+
+        let problems: Vec<(Severity, String)> = checks(&Global::default(), &file)
+            .into_iter()
+            .filter_map(|c| Some((c.problem?, c.message)))
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                (Severity::Error, "line 3 isn't valid UTF-8".to_string()),
+                (
+                    Severity::Warning,
+                    "the file has Windows line endings, commands that rewrite the whole file like fmt drop them".to_string()
+                ),
+                (
+                    Severity::Error,
+                    "line 2: invalid time: input contains invalid characters".to_string()
+                ),
+            ]
+        );
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
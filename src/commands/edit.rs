@@ -0,0 +1,33 @@
+use super::{now_for, read_for_edit, write_file};
+use std::env;
+use std::error::Error;
+use std::path::Path;
+use std::process::Command;
+
+/// Opens `$VISUAL` or `$EDITOR`, falling back to vi, at the end of today's
+/// block. The date header is added first if the file doesn't have it
+pub fn run(file: &Path) -> Result<(), Box<dyn Error>> {
+    let contents = read_for_edit(file)?;
+    let (with_today, line) = timetxt::insert_date(&contents, now_for(&contents)?.date());
+    if with_today != contents {
+        write_file(file, &with_today)?;
+    }
+
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // Editors are often set with flags, Ex. `code --wait`
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or("$EDITOR is empty")?;
+
+    let status = Command::new(program)
+        .args(words)
+        .arg(format!("+{line}"))
+        .arg(file)
+        .status()
+        .map_err(|e| format!("couldn't start {program}: {e}"))?;
+    if !status.success() {
+        return Err(format!("{program} exited with {status}").into());
+    }
+    Ok(())
+}
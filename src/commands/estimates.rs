@@ -0,0 +1,112 @@
+use super::table::{Cell, Table};
+use super::{Global, Inputs};
+use chrono::Duration;
+use clap::{Args, ValueEnum};
+use std::collections::BTreeMap;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct EstimatesArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// What to compare estimates for
+    #[arg(long, value_enum, default_value_t = By::Task)]
+    by: By,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum By {
+    /// Entries with the same description without labels, ignoring case
+    Task,
+    /// The first project of each task
+    Project,
+}
+
+/// Estimated against tracked time for tasks with `est:` metadata. A task's
+/// estimate is the last one given and every entry of the task counts
+/// towards it, so `est:4h` only needs to be written once. Estimates that
+/// aren't durations are reported and left out
+pub fn run(global: &Global, args: &EstimatesArgs) -> Result<(), Box<dyn Error>> {
+    let t = global.read(&args.inputs)?;
+
+    let mut tasks: BTreeMap<String, Task> = BTreeMap::new();
+    for (_, e) in t.iter_entries().filter(|(_, e)| !e.cancelled) {
+        let task = tasks
+            .entry(e.plain_description().to_lowercase())
+            .or_insert_with(|| Task {
+                name: e.plain_description(),
+                project: None,
+                estimate: None,
+                actual: Duration::zero(),
+            });
+        task.actual += e.duration();
+        match (e.meta().get("est"), e.estimate()) {
+            (_, Some(estimate)) => task.estimate = Some(estimate),
+            (Some(est), None) => {
+                eprintln!("bad estimate: {} {e}: {est:?} isn't a duration", e.date)
+            }
+            (None, None) => {}
+        }
+        if let Some(project) = e.projects().first() {
+            task.project.get_or_insert_with(|| format!("+{project}"));
+        }
+    }
+
+    let mut rows: BTreeMap<(bool, String), (Duration, Duration)> = BTreeMap::new();
+    for task in tasks.into_values() {
+        let Some(estimate) = task.estimate else {
+            continue;
+        };
+        let key = match args.by {
+            By::Task => (false, task.name),
+            // Tasks without a project last
+            By::Project => match task.project {
+                Some(project) => (false, project),
+                None => (true, "(none)".to_string()),
+            },
+        };
+        let (estimated, actual) = rows
+            .entry(key)
+            .or_insert((Duration::zero(), Duration::zero()));
+        *estimated += estimate;
+        *actual += task.actual;
+    }
+
+    let group = match args.by {
+        By::Task => "Task",
+        By::Project => "Project",
+    };
+    let mut table = Table::new([group, "Estimate", "Actual", "Variance"]);
+    let (mut estimated, mut actual) = (Duration::zero(), Duration::zero());
+    for ((_, name), (e, a)) in rows {
+        estimated += e;
+        actual += a;
+        table.push(row(Cell::Text(name), e, a));
+    }
+    table.footer(row(Cell::text("Total"), estimated, actual));
+    table.print(global.output)
+}
+
+struct Task {
+    name: String,
+    project: Option<String>,
+    estimate: Option<Duration>,
+    actual: Duration,
+}
+
+/// The variance is how far over the estimate the actual time went, under
+/// it when negative
+fn row(name: Cell, estimate: Duration, actual: Duration) -> Vec<Cell> {
+    let variance = if estimate > Duration::zero() {
+        let over = (actual - estimate).num_minutes();
+        (over * 100 + over.signum() * estimate.num_minutes() / 2) / estimate.num_minutes()
+    } else {
+        0
+    };
+    vec![
+        name,
+        Cell::Duration(estimate),
+        Cell::Duration(actual),
+        Cell::Percent(variance),
+    ]
+}
@@ -0,0 +1,110 @@
+use super::table::Format;
+use super::{Global, Inputs, UsageError};
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use clap::Args;
+use serde_json::{json, Value};
+use std::error::Error;
+use std::io;
+use timetxt::Time;
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// Print Timewarrior intervals for `timew import` instead
+    #[arg(long, conflicts_with = "format")]
+    timewarrior: bool,
+}
+
+/// Prints every day and entry in range for other tools, JSON unless
+/// `--format csv` is given. Cancelled entries are left out of every format,
+/// see `Time::to_json` for the JSON schema
+pub fn run(global: &Global, args: &ExportArgs) -> Result<(), Box<dyn Error>> {
+    let t = global.read(&args.inputs)?;
+    if args.timewarrior {
+        println!("{}", serde_json::to_string_pretty(&timewarrior(&t))?);
+        return Ok(());
+    }
+    match global.output.format {
+        Format::Table | Format::Json => println!("{}", t.to_json()),
+        Format::Csv => write_csv(&t)?,
+        _ => return Err(UsageError("export writes --format json or csv".to_string()).into()),
+    }
+    Ok(())
+}
+
+/// One row per entry for spreadsheets, with the duration in minutes,
+/// projects and tags without their `+` and `#` separated by spaces and
+/// the description without labels or metadata. Cancelled entries are
+/// left out and running ones have no end
+fn write_csv(t: &Time) -> Result<(), Box<dyn Error>> {
+    let mut w = csv::Writer::from_writer(io::stdout());
+    w.write_record([
+        "date",
+        "start",
+        "end",
+        "duration",
+        "project",
+        "tags",
+        "description",
+    ])?;
+    for (date, e) in t.iter_entries().filter(|(_, e)| !e.cancelled) {
+        w.write_record([
+            date.format("%Y-%m-%d").to_string(),
+            e.start.format("%H:%M").to_string(),
+            e.end
+                .map(|end| end.format("%H:%M").to_string())
+                .unwrap_or_default(),
+            e.duration().num_minutes().to_string(),
+            e.projects().join(" "),
+            e.tags().join(" "),
+            e.plain_description(),
+        ])?;
+    }
+    w.flush()?;
+    Ok(())
+}
+
+/// The intervals `timew export` prints, times in UTC. The tags are the
+/// description without labels or metadata followed by each label and
+/// `key:value` as written, Ex. `["Fixed login", "+web", "#bug"]`.
+/// Cancelled entries are left out and running ones have no end
+fn timewarrior(t: &Time) -> Vec<Value> {
+    let utc = |time: NaiveDateTime| {
+        Local
+            .from_local_datetime(&time)
+            .earliest()
+            .map_or_else(|| Utc.from_utc_datetime(&time), |t| t.with_timezone(&Utc))
+            .format("%Y%m%dT%H%M%SZ")
+            .to_string()
+    };
+
+    let entries: Vec<_> = t.iter_entries().filter(|(_, e)| !e.cancelled).collect();
+    // Like timew, the latest interval has id 1
+    let count = entries.len();
+    entries
+        .into_iter()
+        .enumerate()
+        .map(|(i, (_, e))| {
+            let plain = e.plain_description();
+            let plain_words: Vec<&str> = plain.split_whitespace().collect();
+            let labels = e
+                .description
+                .split_whitespace()
+                .filter(|w| !plain_words.contains(w))
+                .map(str::to_string);
+            let tags: Vec<String> = Some(plain.clone())
+                .filter(|p| !p.is_empty())
+                .into_iter()
+                .chain(labels)
+                .collect();
+
+            let mut interval = json!({ "id": count - i, "start": utc(e.start_datetime()) });
+            if let Some(end) = e.end_datetime() {
+                interval["end"] = Value::from(utc(end));
+            }
+            interval["tags"] = Value::from(tags);
+            interval
+        })
+        .collect()
+}
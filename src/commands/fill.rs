@@ -0,0 +1,156 @@
+//! `timetxt fill`, adding the recurring entries from the config's
+//! `templates`
+
+use super::{now_for, read_for_edit, write_file, FileError, Global, UsageError};
+use chrono::{Datelike, NaiveDate, NaiveTime, Weekday};
+use clap::Args;
+use std::error::Error;
+use std::str::FromStr;
+use timetxt::{TimeEntry, Week};
+
+/// An entry that repeats on some days of the week, Ex.
+/// `standup 09:30 09:45 every weekday`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub description: String,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub days: Vec<Weekday>,
+}
+
+impl Template {
+    pub fn entry(&self, date: NaiveDate) -> Result<TimeEntry, Box<dyn Error>> {
+        Ok(TimeEntry::builder()
+            .date(date)
+            .start(self.start)?
+            .end(self.end)?
+            .description(&self.description)
+            .build()?)
+    }
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    /// The description and times in either order like an entry line, then
+    /// `every` and `day`, `weekday`, `weekend` or days of the week
+    /// separated by commas, Ex. `09:00 09:15 plan the day every mon,thu`
+    fn from_str(s: &str) -> Result<Template, String> {
+        let invalid = |why: &str| format!("{s:?} isn't a template, {why}");
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let every = words
+            .iter()
+            .rposition(|w| w.eq_ignore_ascii_case("every"))
+            .ok_or_else(|| invalid("it needs every and the days it repeats on"))?;
+        let (entry, days) = words.split_at(every);
+
+        let clock = |w: &str| NaiveTime::parse_from_str(w, "%H:%M").ok();
+        let (start, end, description) = match entry {
+            [start, end, rest @ ..] if clock(start).is_some() && clock(end).is_some() => {
+                (clock(start), clock(end), rest)
+            }
+            [rest @ .., start, end] => (clock(start), clock(end), rest),
+            _ => (None, None, entry),
+        };
+        let (Some(start), Some(end)) = (start, end) else {
+            return Err(invalid("it needs a start and end time like 09:30 09:45"));
+        };
+        if description.is_empty() {
+            return Err(invalid("it needs a description"));
+        }
+
+        let days = parse_days(&days[1..].join(""))
+            .ok_or_else(|| invalid("every needs day, weekday, weekend or days like mon,thu"))?;
+        Ok(Template {
+            description: description.join(" "),
+            start,
+            end,
+            days,
+        })
+    }
+}
+
+fn parse_days(s: &str) -> Option<Vec<Weekday>> {
+    const WEEKDAYS: [Weekday; 5] = [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+    ];
+    let mut days = Vec::new();
+    for day in s.to_lowercase().split(',').filter(|d| !d.is_empty()) {
+        match day {
+            "day" => days.extend(WEEKDAYS.iter().chain(&[Weekday::Sat, Weekday::Sun])),
+            "weekday" => days.extend(&WEEKDAYS),
+            "weekend" => days.extend(&[Weekday::Sat, Weekday::Sun]),
+            day => days.push(day.parse().ok()?),
+        }
+    }
+    if days.is_empty() {
+        None
+    } else {
+        Some(days)
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct FillArgs {
+    /// Day to fill as YYYY-MM-DD [default: today]
+    #[arg(short, long)]
+    date: Option<NaiveDate>,
+    /// Fill the whole week of the day
+    #[arg(long)]
+    week: bool,
+}
+
+/// Adds each template's entry to the days it repeats on. Days with a
+/// marker are skipped, so are entries already in the file
+pub fn run(global: &Global, args: &FillArgs) -> Result<(), Box<dyn Error>> {
+    if global.templates.is_empty() {
+        return Err(UsageError(
+            "no templates, add some to the config, Ex. templates = [\"standup 09:30 09:45 every weekday\"]"
+                .to_string(),
+        )
+        .into());
+    }
+    let file = global.single_file()?;
+    let mut contents = read_for_edit(&file)?;
+    let date = match args.date {
+        Some(date) => date,
+        None => now_for(&contents)?.date(),
+    };
+    let days: Vec<NaiveDate> = if args.week {
+        Week::starting_on(date, global.week_start)
+            .iter_days()
+            .collect()
+    } else {
+        vec![date]
+    };
+
+    let existing = timetxt::parse_time(&contents).map_err(|e| FileError::new(&file, e))?;
+    let mut added = 0;
+    for &date in &days {
+        if existing.marker(date).is_some() {
+            continue;
+        }
+        for template in &global.templates {
+            if !template.days.contains(&date.weekday()) {
+                continue;
+            }
+            let entry = template.entry(date)?;
+            if existing.entries_for(date).contains(&entry) {
+                continue;
+            }
+            contents = timetxt::insert_entry(&contents, &entry);
+            println!("{} {entry}", global.date(date));
+            added += 1;
+        }
+    }
+    if added == 0 {
+        println!("Nothing to fill");
+        return Ok(());
+    }
+    write_file(&file, &contents)?;
+    Ok(())
+}
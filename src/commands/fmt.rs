@@ -0,0 +1,65 @@
+use super::{display_name, is_stdin, read_contents, write_file, FileError, Global, Inputs};
+use clap::Args;
+use std::error::Error;
+use std::path::Path;
+use std::process;
+use timetxt::ParseError;
+
+#[derive(Debug, Args)]
+pub struct FmtArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// Only report whether files need rewriting, exiting with 1 if any do
+    #[arg(long)]
+    check: bool,
+}
+
+/// Formats each file in place, standard input is formatted to standard output
+pub fn run(global: &Global, args: &FmtArgs) -> Result<(), Box<dyn Error>> {
+    rewrite(global, args, timetxt::format_time_txt, "formatting")
+}
+
+/// Runs `rewrite` on each file like `run` does with `format_time_txt`,
+/// `needs` saying what `--check` found missing
+pub fn rewrite(
+    global: &Global,
+    args: &FmtArgs,
+    rewrite: fn(&str) -> Result<String, ParseError>,
+    needs: &str,
+) -> Result<(), Box<dyn Error>> {
+    let mut unformatted = false;
+    for file in args.inputs.resolve(global)? {
+        unformatted |= !rewrite_file(&file, args.check, rewrite, needs)?;
+    }
+
+    if unformatted {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+/// Returns false if `check` is set and the file isn't rewritten yet
+fn rewrite_file(
+    file: &Path,
+    check: bool,
+    rewrite: fn(&str) -> Result<String, ParseError>,
+    needs: &str,
+) -> Result<bool, Box<dyn Error>> {
+    let contents = read_contents(file)?;
+    let formatted = rewrite(&contents).map_err(|e| FileError::new(file, e))?;
+
+    if is_stdin(file) && !check {
+        print!("{formatted}");
+        return Ok(true);
+    }
+    if formatted == contents {
+        return Ok(true);
+    }
+    if check {
+        println!("{} needs {needs}", display_name(file));
+        return Ok(false);
+    }
+
+    write_file(file, &formatted)?;
+    Ok(true)
+}
@@ -0,0 +1,69 @@
+use super::table::{Cell, Table};
+use super::{read_times, Global, Inputs};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Weekday};
+use clap::Args;
+use std::error::Error;
+use timetxt::DateRange;
+
+#[derive(Debug, Args)]
+pub struct GapsArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// Only list gaps at least this long, Ex. 30m
+    #[arg(long, default_value = "15m", value_parser = timetxt::parse_duration)]
+    min: Duration,
+}
+
+/// Gaps between the entries of each day, and weekdays without entries or
+/// a marker from the first tracked day up to today
+pub fn run(global: &Global, args: &GapsArgs) -> Result<(), Box<dyn Error>> {
+    let all = global.adjust(read_times(&args.inputs.resolve(global)?)?)?;
+    let Some(first) = all.days().next() else {
+        return Table::new(["Date", "From", "To", "Duration"]).print(global.output);
+    };
+    let range = global
+        .range
+        .unwrap_or_else(|| DateRange::new(NaiveDate::MIN, NaiveDate::MAX));
+    let range = DateRange::new(
+        range.start().max(first),
+        range.end().min(global.now().date()),
+    );
+    let t = all.range(range);
+
+    let mut rows: Vec<(NaiveDate, Vec<Cell>)> = Vec::new();
+    let time = |t: NaiveDateTime| Cell::text(t.format("%H:%M").to_string());
+    for gap in t.gaps(args.min) {
+        rows.push((
+            gap.date,
+            vec![
+                Cell::text(global.date(gap.date)),
+                time(gap.start),
+                time(gap.end),
+                Cell::Duration(gap.duration()),
+            ],
+        ));
+    }
+    for date in all.untracked_days(range) {
+        // Holidays after the last tracked day have no marker
+        if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) || global.is_holiday(date) {
+            continue;
+        }
+        rows.push((
+            date,
+            vec![
+                Cell::text(global.date(date)),
+                Cell::text(""),
+                Cell::text(""),
+                Cell::text("untracked"),
+            ],
+        ));
+    }
+    // Stable, so a day's gaps stay in order
+    rows.sort_by_key(|(date, _)| *date);
+
+    let mut table = Table::new(["Date", "From", "To", "Duration"]);
+    for (_, row) in rows {
+        table.push(row);
+    }
+    table.print(global.output)
+}
@@ -0,0 +1,148 @@
+use super::table::{Cell, Table};
+use super::{format_duration, read_times, Global, Inputs, UsageError};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use clap::Args;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use timetxt::{DateRange, Time, Week};
+
+/// Hours to track each day or week, Ex. `40h/week` or `7:30/day`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Goal {
+    pub hours: Duration,
+    pub per: Per,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Per {
+    Day,
+    Week,
+}
+
+impl FromStr for Goal {
+    type Err = String;
+
+    /// A duration for `parse_duration` with `/day` or `/week` after it,
+    /// a duration alone is a daily goal
+    fn from_str(s: &str) -> Result<Goal, String> {
+        let invalid = || format!("{s:?} isn't a goal like 40h/week or 8h/day");
+        let (hours, per) = s.split_once('/').unwrap_or((s, "day"));
+        let per = match per.trim().to_lowercase().as_str() {
+            "day" | "d" => Per::Day,
+            "week" | "w" => Per::Week,
+            _ => return Err(invalid()),
+        };
+        let hours = timetxt::parse_duration(hours).map_err(|_| invalid())?;
+        Ok(Goal { hours, per })
+    }
+}
+
+impl fmt::Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let per = match self.per {
+            Per::Day => "day",
+            Per::Week => "week",
+        };
+        write!(f, "{} a {per}", format_duration(self.hours))
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct GoalArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// The goal to measure against [default: the configured goal]
+    #[arg(long, value_parser = Goal::from_str)]
+    goal: Option<Goal>,
+}
+
+/// Progress towards the goal for today or this week, what's left and when
+/// today's share is done if work goes on from now
+pub fn run(global: &Global, args: &GoalArgs) -> Result<(), Box<dyn Error>> {
+    let goal = args.goal.or(global.goal).ok_or_else(|| {
+        UsageError(
+            "no goal, pass --goal or set one in the config, Ex. goal = \"40h/week\"".to_string(),
+        )
+    })?;
+    let t = global.adjust(read_times(&args.inputs.resolve(global)?)?)?;
+    let now = global.now();
+    let today = now.date();
+
+    let tracked_on = |date: NaiveDate| -> Duration {
+        t.entries_for(date)
+            .iter()
+            .filter(|e| !e.cancelled)
+            .map(|e| e.duration_at(now))
+            .sum()
+    };
+    let period = match goal.per {
+        Per::Day => DateRange::day(today),
+        Per::Week => Week::starting_on(today, global.week_start).into(),
+    };
+    let tracked: Duration = period
+        .iter_days()
+        .filter(|d| *d <= today)
+        .map(tracked_on)
+        .sum();
+    let tracked_today = tracked_on(today);
+
+    // What's left of a weekly goal is spread over the working days left
+    let today_goal = match goal.per {
+        Per::Day => goal.hours,
+        Per::Week => {
+            let before = tracked - tracked_today;
+            let days = working_days(global, &t, DateRange::new(today, period.end())).max(1);
+            (goal.hours - before).max(Duration::zero()) / days
+        }
+    };
+    let left_today = (today_goal - tracked_today).max(Duration::zero());
+
+    let percent = if goal.hours > Duration::zero() {
+        tracked.num_minutes() * 100 / goal.hours.num_minutes()
+    } else {
+        100
+    };
+    let finish = if left_today > Duration::zero() {
+        (now + left_today).format("%H:%M").to_string()
+    } else {
+        "done".to_string()
+    };
+
+    let mut table = Table::new(["Progress", "Value"]);
+    table.push(vec![Cell::text("Goal"), Cell::Text(goal.to_string())]);
+    table.push(vec![
+        Cell::text("Tracked"),
+        Cell::Text(format!("{} ({percent}%)", format_duration(tracked))),
+    ]);
+    table.push(vec![
+        Cell::text("Remaining"),
+        Cell::Text(format_duration(
+            (goal.hours - tracked).max(Duration::zero()),
+        )),
+    ]);
+    if goal.per == Per::Week {
+        table.push(vec![
+            Cell::text("Today"),
+            Cell::Text(format!(
+                "{} of {}",
+                format_duration(tracked_today),
+                format_duration(today_goal)
+            )),
+        ]);
+    }
+    table.push(vec![Cell::text("Finish at"), Cell::Text(finish)]);
+    table.print(global.output)
+}
+
+/// Weekdays in `range` without a vacation, sick or holiday marker. Holidays
+/// after the last tracked day have no marker yet
+fn working_days(global: &Global, t: &Time, range: DateRange) -> i32 {
+    let days = range
+        .iter_days()
+        .filter(|d| !matches!(d.weekday(), Weekday::Sat | Weekday::Sun))
+        .filter(|d| t.marker(*d).is_none() && !global.is_holiday(*d))
+        .count();
+    i32::try_from(days).unwrap_or(i32::MAX)
+}
@@ -0,0 +1,628 @@
+//! `timetxt import`, turning other tools' exports into time.txt entries
+
+use super::{read_contents, read_for_edit, write_file, FileError, Global};
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use clap::{Args, ValueEnum};
+use serde::Deserialize;
+use std::error::Error;
+use std::path::PathBuf;
+use timetxt::{Time, TimeEntry};
+
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// The export to read, - for standard input
+    source: PathBuf,
+    /// What kind of export it is
+    #[arg(long = "type", value_enum, default_value_t = Source::Csv)]
+    kind: Source,
+    /// Add the entries to the time.txt file instead of printing them.
+    /// Entries already in it are skipped
+    #[arg(long)]
+    append: bool,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Source {
+    /// Date, Start, End and Description columns in any order, like the
+    /// entries printed with `--format csv`
+    Csv,
+    /// iCalendar events, Ex. a calendar exported as .ics. All day and
+    /// multi-day events are skipped and repeating ones only give their
+    /// first occurrence
+    Ics,
+    /// The intervals printed by `timew export`
+    Timewarrior,
+    /// A Toggl Track detailed report exported as CSV
+    Toggl,
+}
+
+/// Prints the imported entries as time.txt blocks, or with `--append`
+/// adds the new ones to the file and says how many there were. Only
+/// entries in `--period` or `--from` and `--to` are imported
+pub fn run(global: &Global, args: &ImportArgs) -> Result<(), Box<dyn Error>> {
+    let contents = read_contents(&args.source)?;
+    let entries = match args.kind {
+        Source::Csv => csv_entries(&contents),
+        Source::Ics => ics_entries(&contents),
+        Source::Timewarrior => timewarrior_entries(&contents),
+        Source::Toggl => toggl_entries(&contents),
+    }
+    .map_err(|e| FileError::new(&args.source, e))?;
+    let entries: Vec<TimeEntry> = entries
+        .into_iter()
+        .filter(|e| global.range.is_none_or(|range| range.contains(e.date)))
+        .collect();
+
+    if !args.append {
+        let mut t = Time::new();
+        for e in entries {
+            t.add_entry(e)?;
+        }
+        print!("{}", t.to_time_txt());
+        return Ok(());
+    }
+
+    let file = global.single_file()?;
+    let mut contents = read_for_edit(&file)?;
+    let existing = timetxt::parse_time(&contents).map_err(|e| FileError::new(&file, e))?;
+    let (mut added, mut skipped) = (0, 0);
+    for e in entries {
+        if existing.entries_for(e.date).contains(&e) {
+            skipped += 1;
+            continue;
+        }
+        contents = timetxt::insert_entry(&contents, &e);
+        added += 1;
+    }
+    write_file(&file, &contents)?;
+    println!(
+        "Imported {added} {} into {}, skipped {skipped} already there",
+        if added == 1 { "entry" } else { "entries" },
+        file.display()
+    );
+    Ok(())
+}
+
+/// Rows with a `Status` of `cancelled` are cancelled and those without an
+/// end are left running. Other columns are ignored
+fn csv_entries(contents: &str) -> Result<Vec<TimeEntry>, Box<dyn Error>> {
+    let mut reader = csv::Reader::from_reader(contents.as_bytes());
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name))
+    };
+    let columns = CsvColumns {
+        date: column("date").ok_or("no Date column")?,
+        start: column("start").ok_or("no Start column")?,
+        end: column("end"),
+        description: column("description"),
+        status: column("status"),
+    };
+
+    let mut entries = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        // The header is line 1
+        let entry = columns
+            .entry(&record?)
+            .map_err(|e| format!("line {}: {e}", i + 2))?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+struct CsvColumns {
+    date: usize,
+    start: usize,
+    end: Option<usize>,
+    description: Option<usize>,
+    status: Option<usize>,
+}
+
+impl CsvColumns {
+    fn entry(&self, record: &csv::StringRecord) -> Result<TimeEntry, Box<dyn Error>> {
+        let field = |i: Option<usize>| i.and_then(|i| record.get(i)).unwrap_or("").trim();
+
+        let date = field(Some(self.date));
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("bad date {date:?}"))?;
+        let mut entry = TimeEntry::builder()
+            .date(date)
+            .start(parse_clock(field(Some(self.start)))?)?;
+        if !field(self.end).is_empty() {
+            entry = entry.end(parse_clock(field(self.end))?)?;
+        }
+        Ok(entry
+            .description(field(self.description))
+            .cancelled(field(self.status).eq_ignore_ascii_case("cancelled"))
+            .build()?)
+    }
+}
+
+/// `09:30` or `09:30:15`, seconds are dropped
+fn parse_clock(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
+        .ok()
+        .and_then(|t| t.with_second(0))
+        .ok_or_else(|| format!("bad time {s:?}"))
+}
+
+/// Every VEVENT with a start and an end or duration, its SUMMARY as the
+/// description and times in the local zone. Cancelled events are
+/// imported cancelled, attendees and everything else are ignored
+fn ics_entries(contents: &str) -> Result<Vec<TimeEntry>, Box<dyn Error>> {
+    // Long lines are folded onto lines starting with a space or tab
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some((_, last))) => last.push_str(rest),
+            _ => lines.push((i + 1, line.to_string())),
+        }
+    }
+
+    let mut entries = Vec::new();
+    let mut event: Option<(usize, IcsEvent)> = None;
+    for (number, line) in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, params) = name.split_once(';').unwrap_or((name, ""));
+        match (name.to_ascii_uppercase().as_str(), &mut event) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                event = Some((*number, IcsEvent::default()));
+            }
+            ("END", Some((start_line, e))) if value.eq_ignore_ascii_case("VEVENT") => {
+                let entry = e
+                    .entry()
+                    .map_err(|err| format!("event on line {start_line}: {err}"))?;
+                entries.extend(entry);
+                event = None;
+            }
+            ("DTSTART", Some((_, e))) => e.start = ics_time(params, value),
+            ("DTEND", Some((_, e))) => e.end = ics_time(params, value),
+            ("DURATION", Some((_, e))) => e.duration = ics_duration(value),
+            ("SUMMARY", Some((_, e))) => e.summary = ics_text(value),
+            ("STATUS", Some((_, e))) => e.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            _ => {}
+        }
+    }
+    Ok(entries)
+}
+
+#[derive(Default)]
+struct IcsEvent {
+    /// None for all day events and times that can't be read
+    start: Option<NaiveDateTime>,
+    end: Option<NaiveDateTime>,
+    duration: Option<chrono::Duration>,
+    summary: String,
+    cancelled: bool,
+}
+
+impl IcsEvent {
+    /// None for events that don't make an entry
+    fn entry(&self) -> Result<Option<TimeEntry>, Box<dyn Error>> {
+        let Some(start) = self.start else {
+            return Ok(None);
+        };
+        let Some(end) = self.end.or_else(|| Some(start + self.duration?)) else {
+            return Ok(None);
+        };
+        // An entry can run past midnight but not for a day or more
+        if end <= start || end - start >= chrono::Duration::days(1) {
+            return Ok(None);
+        }
+        Ok(Some(
+            TimeEntry::builder()
+                .date(start.date())
+                .start(start.time())?
+                .end(end.time())?
+                .description(&self.summary)
+                .cancelled(self.cancelled)
+                .build()?,
+        ))
+    }
+}
+
+/// A DTSTART or DTEND value in the local zone, to the minute. UTC times
+/// end with Z, TZID gives the zone of others and times without either
+/// are already local. Dates alone are all day and give None
+fn ics_time(params: &str, value: &str) -> Option<NaiveDateTime> {
+    let (value, utc) = match value.trim().strip_suffix(['Z', 'z']) {
+        Some(value) => (value, true),
+        None => (value.trim(), false),
+    };
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let zone = params
+        .split(';')
+        .find_map(|p| p.strip_prefix("TZID="))
+        .and_then(|zone| zone.trim_matches('"').parse::<Tz>().ok());
+    let local = if utc {
+        Utc.from_utc_datetime(&time)
+            .with_timezone(&Local)
+            .naive_local()
+    } else if let Some(zone) = zone {
+        zone.from_local_datetime(&time)
+            .earliest()?
+            .with_timezone(&Local)
+            .naive_local()
+    } else {
+        time
+    };
+    local.with_second(0)
+}
+
+/// An RFC 5545 duration like `PT1H30M` or `P1D`
+fn ics_duration(value: &str) -> Option<chrono::Duration> {
+    let rest = value.trim().strip_prefix(['P', 'p'])?;
+    let mut total = chrono::Duration::zero();
+    let mut number = String::new();
+    for c in rest.chars() {
+        match c.to_ascii_uppercase() {
+            'T' => {}
+            '0'..='9' => number.push(c),
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match unit {
+                    'W' => chrono::Duration::weeks(n),
+                    'D' => chrono::Duration::days(n),
+                    'H' => chrono::Duration::hours(n),
+                    'M' => chrono::Duration::minutes(n),
+                    'S' => chrono::Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(total)
+}
+
+/// Undoes the escaping of TEXT values and puts multiple lines on one
+fn ics_text(value: &str) -> String {
+    let mut text = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push(' '),
+            Some(escaped) => text.push(escaped),
+            None => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[derive(Deserialize)]
+struct Interval {
+    id: Option<u64>,
+    start: String,
+    end: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    annotation: Option<String>,
+}
+
+/// Intervals as entries in the local zone. The annotation is the
+/// description, or without one the first tag that isn't a label. Tags
+/// written like labels or `key:value` are kept as they are and other
+/// tags become `#tags`, with dashes for spaces. Intervals without an end
+/// are left running and those shorter than a minute are skipped
+fn timewarrior_entries(contents: &str) -> Result<Vec<TimeEntry>, Box<dyn Error>> {
+    let intervals: Vec<Interval> = serde_json::from_str(contents)?;
+    let mut entries = Vec::new();
+    for (i, interval) in intervals.iter().enumerate() {
+        let id = interval
+            .id
+            .map_or_else(|| format!("{}", i + 1), |id| id.to_string());
+        let entry = timewarrior_entry(interval).map_err(|e| format!("interval {id}: {e}"))?;
+        entries.extend(entry);
+    }
+    Ok(entries)
+}
+
+/// None for intervals that start and end in the same minute
+fn timewarrior_entry(interval: &Interval) -> Result<Option<TimeEntry>, Box<dyn Error>> {
+    let time = |s: &str| {
+        NaiveDateTime::parse_from_str(s.trim_end_matches(['Z', 'z']), "%Y%m%dT%H%M%S")
+            .map(|t| {
+                let local = Utc
+                    .from_utc_datetime(&t)
+                    .with_timezone(&Local)
+                    .naive_local();
+                local.with_second(0).unwrap_or(local)
+            })
+            .map_err(|_| format!("bad time {s:?}"))
+    };
+    let start = time(&interval.start)?;
+    let end = interval.end.as_deref().map(time).transpose()?;
+    match end {
+        Some(end) if end < start => return Err("the interval ends before it starts".into()),
+        Some(end) if end == start => return Ok(None),
+        Some(end) if end - start >= chrono::Duration::days(1) => {
+            return Err("an entry can't be a day or longer, split the interval first".into());
+        }
+        _ => {}
+    }
+
+    let is_label = |tag: &str| {
+        tag.starts_with(['#', '+', '@'])
+            || tag
+                .split_once(':')
+                .is_some_and(|(key, value)| !key.is_empty() && !value.is_empty())
+    };
+    let mut words: Vec<String> = interval.annotation.iter().cloned().collect();
+    let mut plain_tags = interval.tags.iter().filter(|t| !is_label(t.as_str()));
+    if words.is_empty() {
+        words.extend(plain_tags.next().cloned());
+    }
+    for tag in plain_tags {
+        words.push(format!(
+            "#{}",
+            tag.split_whitespace().collect::<Vec<_>>().join("-")
+        ));
+    }
+    words.extend(
+        interval
+            .tags
+            .iter()
+            .filter(|t| is_label(t.as_str()))
+            .cloned(),
+    );
+
+    let mut entry = TimeEntry::builder()
+        .date(start.date())
+        .start(start.time())?;
+    if let Some(end) = end {
+        entry = entry.end(end.time())?;
+    }
+    Ok(Some(entry.description(&words.join(" ")).build()?))
+}
+
+/// Rows of a detailed report with the description followed by the
+/// project as a `+project`, the tags as `#tags`, the client as `client:`
+/// and whether it's billable as `billable:` metadata. Spaces in labels
+/// and metadata become dashes. Entries shorter than a minute are skipped
+fn toggl_entries(contents: &str) -> Result<Vec<TimeEntry>, Box<dyn Error>> {
+    let mut reader = csv::Reader::from_reader(contents.as_bytes());
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name))
+    };
+    let required = |name: &str| column(name).ok_or_else(|| format!("no {name} column"));
+    let columns = TogglColumns {
+        start_date: required("Start date")?,
+        start_time: required("Start time")?,
+        end_date: required("End date")?,
+        end_time: required("End time")?,
+        description: column("Description"),
+        project: column("Project"),
+        client: column("Client"),
+        tags: column("Tags"),
+        billable: column("Billable"),
+    };
+
+    let mut entries = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        // The header is line 1
+        let entry = columns
+            .entry(&record?)
+            .map_err(|e| format!("line {}: {e}", i + 2))?;
+        entries.extend(entry);
+    }
+    Ok(entries)
+}
+
+struct TogglColumns {
+    start_date: usize,
+    start_time: usize,
+    end_date: usize,
+    end_time: usize,
+    description: Option<usize>,
+    project: Option<usize>,
+    client: Option<usize>,
+    tags: Option<usize>,
+    billable: Option<usize>,
+}
+
+impl TogglColumns {
+    /// None for entries that start and end in the same minute
+    fn entry(&self, record: &csv::StringRecord) -> Result<Option<TimeEntry>, Box<dyn Error>> {
+        let field = |i: Option<usize>| i.and_then(|i| record.get(i)).unwrap_or("").trim();
+        let dashed = |s: &str| s.split_whitespace().collect::<Vec<_>>().join("-");
+        let at = |date: usize, time: usize| -> Result<NaiveDateTime, String> {
+            let date = field(Some(date));
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .or_else(|_| NaiveDate::parse_from_str(date, "%m/%d/%Y"))
+                .map_err(|_| format!("bad date {date:?}"))?;
+            Ok(date.and_time(parse_clock(field(Some(time)))?))
+        };
+        let start = at(self.start_date, self.start_time)?;
+        let end = at(self.end_date, self.end_time)?;
+        if end < start {
+            return Err("the entry ends before it starts".into());
+        }
+        if end == start {
+            return Ok(None);
+        }
+        if end - start >= chrono::Duration::days(1) {
+            return Err("an entry can't be a day or longer".into());
+        }
+
+        let mut words = vec![field(self.description).to_string()];
+        if !field(self.project).is_empty() {
+            words.push(format!("+{}", dashed(field(self.project))));
+        }
+        words.extend(
+            field(self.tags)
+                .split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(|t| format!("#{}", dashed(t))),
+        );
+        if !field(self.client).is_empty() {
+            words.push(format!("client:{}", dashed(field(self.client))));
+        }
+        match field(self.billable).to_lowercase().as_str() {
+            "yes" | "true" => words.push("billable:yes".to_string()),
+            "no" | "false" => words.push("billable:no".to_string()),
+            _ => {}
+        }
+        let description = words.join(" ");
+
+        Ok(Some(
+            TimeEntry::builder()
+                .date(start.date())
+                .start(start.time())?
+                .end(end.time())?
+                .description(description.trim())
+                .build()?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(s: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(s, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_ics_entries() -> Result<(), Box<dyn Error>> {
+        let ics = "BEGIN:VCALENDAR\r\n\
+        BEGIN:VEVENT\r\n\
+        DTSTART:20240304T090000\r\n\
+        DTEND:20240304T103015\r\n\
+        SUMMARY:Planning\\, with the\r\n  team\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        DTSTART:20240304T230000\r\n\
+        DURATION:PT1H30M\r\n\
+        SUMMARY:Deploy\r\n\
+        STATUS:CANCELLED\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        DTSTART;VALUE=DATE:20240305\r\n\
+        SUMMARY:All day\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        DTSTART:20240306T090000\r\n\
+        DTEND:20240307T090000\r\n\
+        SUMMARY:A whole day\r\n\
+        END:VEVENT\r\n\
+        END:VCALENDAR\r\n";
+
+        let entries = ics_entries(ics)?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0].date,
+            NaiveDate::from_ymd_opt(2024, 3, 4).unwrap()
+        );
+        assert_eq!(entries[0].start, time("09:00"));
+        assert_eq!(entries[0].end, Some(time("10:30")));
+        assert_eq!(entries[0].description, "Planning, with the team");
+        assert!(!entries[0].cancelled);
+        assert_eq!(entries[1].start, time("23:00"));
+        assert_eq!(entries[1].end, Some(time("00:30")));
+        assert!(entries[1].cancelled);
+        Ok(())
+    }
+
+    #[test]
+    fn test_timewarrior_entries() -> Result<(), Box<dyn Error>> {
+        let json = r#"[
+            {"id":3,"start":"20240304T090000Z","end":"20240304T103015Z","tags":["web","fix login","+acme","client:babbage"]},
+            {"id":2,"start":"20240304T110010Z","end":"20240304T110040Z","tags":["blink"]},
+            {"id":1,"start":"20240304T120000Z","tags":["review"],"annotation":"Review PR"}
+        ]"#;
+        let local = |s: &str| {
+            let t = NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M").unwrap();
+            Utc.from_utc_datetime(&t)
+                .with_timezone(&Local)
+                .naive_local()
+        };
+
+        let entries = timewarrior_entries(json)?;
+        // The 30 second interval is skipped
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].start_datetime(), local("20240304T0900"));
+        assert_eq!(entries[0].duration(), chrono::Duration::minutes(90));
+        assert_eq!(
+            entries[0].description,
+            "web #fix-login +acme client:babbage"
+        );
+        assert_eq!(entries[1].start_datetime(), local("20240304T1200"));
+        assert!(entries[1].is_open());
+        assert_eq!(entries[1].description, "Review PR #review");
+        Ok(())
+    }
+
+    #[test]
+    fn test_timewarrior_entries_errors() {
+        let backwards = r#"[{"id":1,"start":"20240304T100000Z","end":"20240304T090000Z"}]"#;
+        let err = timewarrior_entries(backwards).unwrap_err().to_string();
+        assert_eq!(err, "interval 1: the interval ends before it starts");
+
+        let long = r#"[{"id":7,"start":"20240304T090000Z","end":"20240305T090000Z"}]"#;
+        let err = timewarrior_entries(long).unwrap_err().to_string();
+        assert!(err.starts_with("interval 7: "), "{}", err);
+    }
+
+    const TOGGL_HEADER: &str = "User,Email,Client,Project,Task,Description,Billable,\
+        Start date,Start time,End date,End time,Duration,Tags,Amount ()\n";
+
+    #[test]
+    fn test_toggl_entries() -> Result<(), Box<dyn Error>> {
+        let csv = format!(
+            "{}{}{}{}",
+            TOGGL_HEADER,
+            "Jane,j@x.io,ACME Corp,Web Site,,Fix login,Yes,\
+            2024-03-04,09:00:00,2024-03-04,10:30:15,01:30:15,\"bug, urgent fix\",\n",
+            "Jane,j@x.io,,,,Deploy,No,03/04/2024,23:30:00,03/05/2024,00:15:00,00:45:00,,\n",
+            "Jane,j@x.io,,,,Blink,No,2024-03-04,11:00:10,2024-03-04,11:00:40,00:00:30,,\n",
+        );
+
+        let entries = toggl_entries(&csv)?;
+        // The 30 second entry is skipped
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0].date,
+            NaiveDate::from_ymd_opt(2024, 3, 4).unwrap()
+        );
+        assert_eq!(entries[0].start, time("09:00"));
+        assert_eq!(entries[0].end, Some(time("10:30")));
+        assert_eq!(
+            entries[0].description,
+            "Fix login +Web-Site #bug #urgent-fix client:ACME-Corp billable:yes"
+        );
+        assert_eq!(entries[1].start, time("23:30"));
+        assert_eq!(entries[1].end, Some(time("00:15")));
+        assert_eq!(entries[1].description, "Deploy billable:no");
+        Ok(())
+    }
+
+    #[test]
+    fn test_toggl_entries_errors() {
+        let backwards = format!(
+            "{}{}",
+            TOGGL_HEADER, "Jane,j@x.io,,,,Oops,No,2024-03-04,10:00:00,2024-03-04,09:00:00,,,\n"
+        );
+        let err = toggl_entries(&backwards).unwrap_err().to_string();
+        assert_eq!(err, "line 2: the entry ends before it starts");
+
+        let err = toggl_entries("Description,Start date\n")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "no Start time column");
+    }
+}
@@ -0,0 +1,148 @@
+use super::table::{Cell, Format, Table};
+use super::{Global, Inputs, RoundArgs, UsageError};
+use chrono::Duration;
+use clap::Args;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct InvoiceArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// Amount charged per hour [default: the configured hourly_rate]
+    #[arg(long)]
+    rate: Option<f64>,
+    /// Only bill entries for this client, set with `client:NAME` metadata
+    /// or by the client's projects in the config
+    #[arg(long)]
+    client: Option<String>,
+    /// The invoice number [default: the configured number format filled
+    /// in with today's date]
+    #[arg(long)]
+    number: Option<String>,
+    /// A tax percentage added to the total, Ex. 19 [default: the
+    /// configured tax]
+    #[arg(long, value_name = "PERCENT")]
+    tax: Option<f64>,
+    #[command(flatten)]
+    round: RoundArgs,
+}
+
+/// One line per finished entry with its rounded hours and amount. Use
+/// `--period` or `--from` and `--to` for the billed dates. Tables start
+/// with the number, date and the configured payee and payer
+pub fn run(global: &Global, args: &InvoiceArgs) -> Result<(), Box<dyn Error>> {
+    let config = &global.invoice;
+    let currency = config.currency();
+    let money = |amount: f64| Cell::Money(amount, currency.clone());
+    let rate = args.rate.or(global.hourly_rate).ok_or_else(|| {
+        UsageError("no hourly rate, pass --rate or set hourly_rate in the config".to_string())
+    })?;
+    let t = global.read(&args.inputs)?;
+
+    let mut table = Table::new(["Date", "Description", "Hours", "Amount"]);
+    let mut hours = Duration::zero();
+    let mut amount = 0.0;
+    for (date, e) in t.iter_entries() {
+        if e.cancelled || e.is_open() {
+            continue;
+        }
+        if let Some(client) = &args.client {
+            let billed_to = global.client(e);
+            if !billed_to.is_some_and(|c| c.eq_ignore_ascii_case(client)) {
+                continue;
+            }
+        }
+
+        let billed = args.round.apply(global, e.duration());
+        let line = price(billed, rate);
+        hours += billed;
+        amount += line;
+        table.push(vec![
+            Cell::text(global.date(*date)),
+            Cell::Text(e.plain_description()),
+            Cell::Hours(billed),
+            money(line),
+        ]);
+    }
+    match args.tax.or(config.tax) {
+        Some(tax) => {
+            let tax_amount = (amount * tax).round() / 100.0;
+            let tax_name = config.tax_name.as_deref().unwrap_or("Tax");
+            table.footer(vec![
+                Cell::text("Subtotal"),
+                Cell::text(""),
+                Cell::Hours(hours),
+                money(amount),
+            ]);
+            table.footer(vec![
+                Cell::text(format!("{tax_name} {tax}%")),
+                Cell::text(""),
+                Cell::text(""),
+                money(tax_amount),
+            ]);
+            table.footer(vec![
+                Cell::text("Total"),
+                Cell::text(""),
+                Cell::text(""),
+                money(amount + tax_amount),
+            ]);
+        }
+        None => table.footer(vec![
+            Cell::text("Total"),
+            Cell::text(""),
+            Cell::Hours(hours),
+            money(amount),
+        ]),
+    }
+
+    // CSV and JSON are the entries alone
+    if matches!(global.output.format, Format::Table | Format::Plain) {
+        print_heading(global, args);
+    }
+    table.print(global.output)
+}
+
+/// The number and date, then who is paid and who pays, each followed by
+/// a blank line
+fn print_heading(global: &Global, args: &InvoiceArgs) {
+    let config = &global.invoice;
+    let today = global.now().date();
+    let number = args.number.clone().or_else(|| {
+        config
+            .number
+            .as_ref()
+            .map(|format| today.format(format).to_string())
+    });
+    let payer = args.client.as_ref().and_then(|client| {
+        config
+            .payers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(client))
+            .map(|(_, block)| block)
+    });
+
+    let mut title = "Invoice".to_string();
+    if let Some(number) = &number {
+        title.push_str(&format!(" {number}"));
+    }
+    if let (Some(client), None) = (&args.client, payer) {
+        title.push_str(&format!(" for {client}"));
+    }
+    if number.is_some() || args.client.is_some() {
+        println!("{title}");
+        if number.is_some() {
+            println!("Date: {}", global.date(today));
+        }
+        println!();
+    }
+    for (heading, block) in [("From", config.payee.as_ref()), ("To", payer)] {
+        if let Some(block) = block {
+            println!("{heading}:\n{}\n", block.trim_end());
+        }
+    }
+}
+
+/// The amount for `d` at `rate` per hour, to the cent
+fn price(d: Duration, rate: f64) -> f64 {
+    (rate * d.num_minutes() as f64 / 60.0 * 100.0).round() / 100.0
+}
@@ -0,0 +1,57 @@
+use super::table::{Cell, Table};
+use super::{Global, Inputs};
+use chrono::Duration;
+use std::error::Error;
+
+/// Every entry by date and start time with its labels and the running
+/// total up to and including it. Cancelled entries are listed but don't
+/// add to the total
+pub fn run(global: &Global, inputs: &Inputs) -> Result<(), Box<dyn Error>> {
+    let t = global.read(inputs)?;
+    let now = global.now();
+
+    let mut entries: Vec<_> = t.iter_entries().map(|(_, e)| e).collect();
+    entries.sort_by_key(|e| e.start_datetime());
+
+    let mut table = Table::new([
+        "Date",
+        "Start",
+        "End",
+        "Duration",
+        "Description",
+        "Labels",
+        "Cumulative",
+    ]);
+    let mut total = Duration::zero();
+    for e in entries {
+        let duration = e.duration_at(now);
+        if !e.cancelled {
+            total += duration;
+        }
+        let labels: Vec<String> = e
+            .tags()
+            .into_iter()
+            .map(|l| format!("#{l}"))
+            .chain(e.projects().into_iter().map(|l| format!("+{l}")))
+            .chain(e.contexts().into_iter().map(|l| format!("@{l}")))
+            .collect();
+        let end = match e.end {
+            Some(end) => end.format("%H:%M").to_string(),
+            None => "--:--".to_string(),
+        };
+        let mut description = e.plain_description();
+        if e.cancelled {
+            description.push_str(" (cancelled)");
+        }
+        table.push(vec![
+            Cell::text(global.date(e.date)),
+            Cell::text(e.start.format("%H:%M").to_string()),
+            Cell::Text(end),
+            Cell::Duration(duration),
+            Cell::Text(description),
+            Cell::Text(labels.join(" ")),
+            Cell::Duration(total),
+        ]);
+    }
+    table.print(global.output)
+}
@@ -0,0 +1,28 @@
+use super::FileError;
+use clap::Args;
+use clap_mangen::Man;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, Args)]
+pub struct ManArgs {
+    /// Write timetxt.1 and a timetxt-COMMAND.1 page per subcommand into
+    /// this directory instead of printing timetxt.1
+    #[arg(long, value_name = "DIR")]
+    dir: Option<PathBuf>,
+}
+
+/// Renders roff man pages from the command line definitions in `cli`
+pub fn run(cli: clap::Command, args: &ManArgs) -> Result<(), Box<dyn Error>> {
+    let Some(dir) = &args.dir else {
+        Man::new(cli.disable_help_subcommand(true)).render(&mut io::stdout())?;
+        return Ok(());
+    };
+
+    fs::create_dir_all(dir).map_err(|e| FileError::new(dir, e))?;
+    clap_mangen::generate_to(cli, dir).map_err(|e| FileError::new(dir, e))?;
+    println!("Wrote man pages to {}", dir.display());
+    Ok(())
+}
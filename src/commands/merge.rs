@@ -0,0 +1,47 @@
+use super::{read_times, write_file, Global, Inputs, UsageError};
+use clap::Args;
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, Args)]
+pub struct MergeArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// Write the merged file here instead of to standard output
+    #[arg(long, short, value_name = "FILE")]
+    output: Option<PathBuf>,
+    /// Write the merged file even when entries overlap
+    #[arg(long)]
+    force: bool,
+}
+
+/// Combines the files into one in canonical form. Entries found in more
+/// than one file are only kept once, entries that overlap are reported and
+/// stop the merge unless `--force` is given. Comments aren't carried over
+pub fn run(global: &Global, args: &MergeArgs) -> Result<(), Box<dyn Error>> {
+    let files = args.inputs.resolve(global)?;
+    if files.len() < 2 {
+        return Err(UsageError("merge needs at least two files".to_string()).into());
+    }
+
+    let mut merged = read_times(&files)?;
+    for e in merged.remove_duplicates() {
+        eprintln!("duplicate: {} {e}", e.date);
+    }
+    let overlaps = merged.overlaps();
+    for o in &overlaps {
+        eprintln!("conflict: {} {} overlaps {}", o.date, o.first, o.second);
+    }
+    if !overlaps.is_empty() && !args.force {
+        let count = overlaps.len();
+        let s = if count == 1 { "" } else { "s" };
+        return Err(format!("found {count} overlap{s}, fix them or pass --force").into());
+    }
+
+    let contents = merged.to_time_txt();
+    match &args.output {
+        Some(output) => write_file(output, &contents)?,
+        None => print!("{contents}"),
+    }
+    Ok(())
+}
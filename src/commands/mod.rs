@@ -0,0 +1,764 @@
+//! Subcommands of the timetxt binary, one module each
+
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike, Weekday};
+use chrono_tz::Tz;
+use clap::{Args, ValueEnum};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use table::{paint, Cell, Output, Style, Table};
+use timetxt::{DateRange, DayMarker, MarkerKind, Rounding, SaveOptions, Time, TimeEntry};
+
+pub mod add;
+pub mod amend;
+pub mod archive;
+pub mod billable;
+pub mod cancel;
+pub mod config;
+pub mod dedupe;
+pub mod doctor;
+pub mod edit;
+pub mod estimates;
+pub mod export;
+pub mod fill;
+pub mod fmt;
+pub mod gaps;
+pub mod goal;
+pub mod import;
+pub mod invoice;
+pub mod log;
+pub mod man;
+pub mod merge;
+pub mod note;
+pub mod overlaps;
+pub mod projects;
+pub mod punch;
+pub mod report;
+/// `timetxt continue`, the name is a keyword
+pub mod resume;
+pub mod rotate;
+pub mod search;
+pub mod sort;
+pub mod start;
+pub mod stats;
+pub mod status;
+pub mod stop;
+pub mod summary;
+pub mod table;
+pub mod tags;
+pub mod timesheet;
+pub mod today;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod validate;
+pub mod watch;
+pub mod week;
+
+/// The file read when none is given or configured
+pub const DEFAULT_FILE: &str = "time.txt";
+
+/// Arguments that parse but can't be used, Ex. two files for a command
+/// that edits. The binary exits with 2 for these like it does for
+/// arguments clap rejects
+#[derive(Debug)]
+pub struct UsageError(pub String);
+
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for UsageError {}
+
+/// An error reading, parsing or writing a file, shown after the file's
+/// name, Ex. `time.txt: line 3: missing end time`
+#[derive(Debug)]
+pub struct FileError {
+    file: String,
+    source: Box<dyn Error>,
+}
+
+impl FileError {
+    pub fn new<E: Into<Box<dyn Error>>>(path: &Path, source: E) -> FileError {
+        FileError {
+            file: display_name(path),
+            source: source.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for FileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.file, self.source)
+    }
+}
+
+impl Error for FileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/// Options given before or after any subcommand, with the config file's
+/// defaults for the ones left out
+#[derive(Debug)]
+pub struct Global {
+    /// Files passed with `--file`
+    pub files: Vec<PathBuf>,
+    /// Read or edited when no file is given
+    pub default_file: PathBuf,
+    /// Only days in this range are reported on
+    pub range: Option<DateRange>,
+    pub output: Output,
+    pub week_start: Weekday,
+    /// The month fiscal years start in, 1 for calendar years
+    pub fiscal_year_start: u32,
+    /// Used when `--round` isn't given
+    pub rounding: Option<Rounding>,
+    /// Used when `report --target` isn't given
+    pub workday: Option<Duration>,
+    pub hourly_rate: Option<f64>,
+    pub date_format: String,
+    /// Used when `goal --goal` isn't given
+    pub goal: Option<goal::Goal>,
+    /// Projects whose entries are billable without `billable:` metadata
+    pub billable_projects: Vec<String>,
+    /// The projects of each client
+    pub clients: BTreeMap<String, Vec<String>>,
+    /// Recurring entries for `timetxt fill`
+    pub templates: Vec<fill::Template>,
+    /// Days that expect no work, like a `HOLIDAY` marker
+    pub holidays: Vec<config::Holiday>,
+    /// Currency, tax, numbering and addresses for `timetxt invoice`
+    pub invoice: config::InvoiceConfig,
+    /// Entries are shown in this zone instead of the local one
+    pub timezone: Option<Tz>,
+}
+
+/// Defaults like an empty config and no flags
+#[cfg(test)]
+impl Default for Global {
+    fn default() -> Global {
+        Global {
+            files: Vec::new(),
+            default_file: PathBuf::from("time.txt"),
+            range: None,
+            output: Output::default(),
+            week_start: Weekday::Mon,
+            fiscal_year_start: 1,
+            rounding: None,
+            workday: None,
+            hourly_rate: None,
+            date_format: "%Y-%m-%d".to_string(),
+            goal: None,
+            billable_projects: Vec::new(),
+            clients: BTreeMap::new(),
+            templates: Vec::new(),
+            holidays: Vec::new(),
+            invoice: config::InvoiceConfig::default(),
+            timezone: None,
+        }
+    }
+}
+
+impl Global {
+    /// Reads and merges the input files, keeping only the days in range
+    pub fn read(&self, inputs: &Inputs) -> Result<Time, Box<dyn Error>> {
+        let t = read_times(&inputs.resolve(self)?)?;
+        Ok(self.in_range(self.adjust(t)?))
+    }
+
+    /// The time in `--timezone`, or the local time without it
+    pub fn now(&self) -> NaiveDateTime {
+        now_in(self.timezone)
+    }
+
+    /// Applies `--timezone` and the configured holidays to what was read
+    pub fn adjust(&self, t: Time) -> Result<Time, Box<dyn Error>> {
+        Ok(self.with_holidays(self.in_zone(t)?))
+    }
+
+    /// Moves every entry from the local zone, which `read_time` leaves
+    /// every file in, into `--timezone`
+    fn in_zone(&self, t: Time) -> Result<Time, Box<dyn Error>> {
+        match self.timezone {
+            Some(zone) => shift(&t, |time| local_in(time, zone)),
+            None => Ok(t),
+        }
+    }
+
+    /// Marks the configured holidays between the first and last day, so
+    /// reports don't grow days nobody tracked. Days with their own marker
+    /// keep it
+    fn with_holidays(&self, mut t: Time) -> Time {
+        let (Some(first), Some(last)) = (t.days().next(), t.days().last()) else {
+            return t;
+        };
+        let holiday = Some(DayMarker {
+            kind: MarkerKind::Holiday,
+            half_day: false,
+        });
+        for date in DateRange::new(first, last).iter_days() {
+            if self.is_holiday(date) && t.marker(date).is_none() {
+                t.set_marker(date, holiday);
+            }
+        }
+        t
+    }
+
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays.iter().any(|h| h.is_on(date))
+    }
+
+    /// Who an entry is for, its `client:` metadata or else the configured
+    /// client of its first project that has one
+    pub fn client(&self, e: &TimeEntry) -> Option<String> {
+        if let Some(client) = e.meta().get("client") {
+            return Some((*client).to_string());
+        }
+        e.projects().iter().find_map(|p| {
+            self.clients
+                .iter()
+                .find(|(_, projects)| projects.iter().any(|c| c.eq_ignore_ascii_case(p)))
+                .map(|(client, _)| client.clone())
+        })
+    }
+
+    /// Formats a date for printing, `YYYY-MM-DD` unless configured and
+    /// always for `--porcelain`
+    pub fn date(&self, date: NaiveDate) -> String {
+        if self.output.format == table::Format::Porcelain {
+            return date.format("%Y-%m-%d").to_string();
+        }
+        date.format(&self.date_format).to_string()
+    }
+
+    pub fn in_range(&self, t: Time) -> Time {
+        match self.range {
+            Some(range) => t.range(range),
+            None => t,
+        }
+    }
+
+    /// The one file a command edits
+    pub fn single_file(&self) -> Result<PathBuf, Box<dyn Error>> {
+        match self.files.as_slice() {
+            [] => Ok(self.default_file.clone()),
+            [file] => Ok(expand_home(file)),
+            _ => Err(UsageError("only one file can be edited at a time".to_string()).into()),
+        }
+    }
+}
+
+/// Files for commands that only read, added to the ones passed with
+/// `--file`. Every file is parsed and merged into one `Time`
+#[derive(Debug, Default, Args)]
+pub struct Inputs {
+    /// Files to read, `-` for standard input. Directories are searched for
+    /// *.txt files and quoted glob patterns are expanded
+    /// [default: $TIMETXT_FILE, the configured file or time.txt]
+    #[arg(value_name = "FILE")]
+    files: Vec<PathBuf>,
+}
+
+impl Inputs {
+    /// The `--file` files followed by the positional ones, with
+    /// directories and glob patterns expanded
+    pub fn resolve(&self, global: &Global) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        let mut files = Vec::new();
+        for path in global.files.iter().chain(&self.files) {
+            expand(path, &mut files)?;
+        }
+        if files.is_empty() {
+            expand(&global.default_file, &mut files)?;
+        }
+        Ok(files)
+    }
+}
+
+/// `--round` for reports whose totals are billed
+#[derive(Debug, Default, Args)]
+pub struct RoundArgs {
+    /// Round every printed duration to a multiple of this, Ex. 15m or 6m
+    #[arg(long, value_name = "DURATION", value_parser = timetxt::parse_duration)]
+    round: Option<Duration>,
+    /// Which way --round goes for durations between two steps
+    /// [default: nearest]
+    #[arg(long, value_enum)]
+    round_mode: Option<RoundMode>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundMode {
+    /// To the closest step, halfway rounds up
+    #[default]
+    Nearest,
+    Up,
+    Down,
+}
+
+impl RoundMode {
+    pub fn rounding(self, step: Duration) -> Rounding {
+        match self {
+            RoundMode::Nearest => Rounding::nearest(step),
+            RoundMode::Up => Rounding::up(step),
+            RoundMode::Down => Rounding::down(step),
+        }
+    }
+}
+
+impl RoundArgs {
+    /// The flags with the configured rounding filling in what's missing
+    pub fn rounding(&self, global: &Global) -> Option<Rounding> {
+        let step = self.round.or_else(|| global.rounding.map(|r| r.step))?;
+        Some(match (self.round_mode, global.rounding) {
+            (Some(mode), _) => mode.rounding(step),
+            (None, Some(configured)) => Rounding { step, ..configured },
+            (None, None) => Rounding::nearest(step),
+        })
+    }
+
+    /// `d` rounded, or unchanged without `--round` or a configured one
+    pub fn apply(&self, global: &Global, d: Duration) -> Duration {
+        self.rounding(global).map_or(d, |r| r.round(d))
+    }
+}
+
+/// Replaces a leading `~` with `$HOME`
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Adds the files `path` stands for and the ones they include, sorted so
+/// reports don't depend on the order the file system lists them in. A file
+/// reached twice, by any path, is only added once
+fn expand(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+    expand_included(path, files, &mut Vec::new())
+}
+
+/// Like `expand` for a file included by the canonical paths in
+/// `including`, each by the one before it. Including one of them again
+/// would never end
+fn expand_included(
+    path: &Path,
+    files: &mut Vec<PathBuf>,
+    including: &mut Vec<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    // Quoted patterns reach us without the shell expanding ~
+    let path = &expand_home(path);
+
+    if path.is_dir() {
+        let mut found = Vec::new();
+        find_txt_files(path, &mut found)?;
+        if found.is_empty() {
+            let message = format!("no .txt files found in {}", path.display());
+            return Err(UsageError(message).into());
+        }
+        found.sort();
+        files.extend(found);
+        return Ok(());
+    }
+
+    let pattern = path.to_string_lossy();
+    if !path.exists() && pattern.contains(['*', '?', '[']) {
+        let mut found = glob::glob(&pattern)?.collect::<Result<Vec<_>, _>>()?;
+        if found.is_empty() {
+            return Err(UsageError(format!("no files match {pattern}")).into());
+        }
+        found.sort();
+        for path in found {
+            expand_included(&path, files, including)?;
+        }
+        return Ok(());
+    }
+
+    let path = path.to_path_buf();
+    let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let key = canonical(&path);
+    if including.contains(&key) {
+        let chain: Vec<String> = including
+            .iter()
+            .chain([&key])
+            .map(|p| p.display().to_string())
+            .collect();
+        let message = format!("includes itself, {}", chain.join(" includes "));
+        return Err(FileError::new(&path, message).into());
+    }
+    if files.iter().any(|f| canonical(f) == key) {
+        return Ok(());
+    }
+    files.push(path.clone());
+    if !is_stdin(&path) {
+        // A file that can't be read fails later with a better message
+        if let Ok(contents) = fs::read_to_string(&path) {
+            let dir = path.parent().unwrap_or_else(|| Path::new(""));
+            including.push(key);
+            for include in includes(&contents) {
+                let include = dir.join(expand_home(Path::new(include)));
+                expand_included(&include, files, including)?;
+            }
+            including.pop();
+        }
+    }
+    Ok(())
+}
+
+/// Files named in `// include: FILE` comments, relative to the file they
+/// are in. `timetxt rotate` adds them for the files it splits off
+pub fn includes(contents: &str) -> impl Iterator<Item = &str> {
+    contents.lines().filter_map(|line| {
+        let include = line
+            .trim()
+            .strip_prefix("//")?
+            .trim_start()
+            .strip_prefix("include:")?
+            .trim();
+        Some(include).filter(|i| !i.is_empty())
+    })
+}
+
+fn find_txt_files(dir: &Path, found: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_txt_files(&path, found)?;
+        } else if path.extension().is_some_and(|ext| ext == "txt") {
+            found.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// True for the `-` file name, which reads standard input
+pub fn is_stdin(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+/// The file name to show in messages
+pub fn display_name(path: &Path) -> String {
+    if is_stdin(path) {
+        "<stdin>".to_string()
+    } else {
+        path.display().to_string()
+    }
+}
+
+/// Reads a file, or standard input for `-`
+pub fn read_contents(path: &Path) -> Result<String, FileError> {
+    let contents = if is_stdin(path) {
+        let mut contents = String::new();
+        io::stdin().read_to_string(&mut contents).map(|_| contents)
+    } else {
+        fs::read_to_string(path)
+    };
+    contents.map_err(|e| FileError::new(path, e))
+}
+
+/// Reads and parses a time.txt file, see `parse_file`
+pub fn read_time(path: &Path) -> Result<Time, FileError> {
+    let contents = read_contents(path)?;
+    parse_file(&contents).map_err(|e| FileError::new(path, e))
+}
+
+/// Parses a time.txt file in the local zone. Files written in another
+/// zone say so in a comment before their first day, Ex.
+/// `// timezone: Europe/Berlin`, and their entries are moved into the
+/// local zone
+pub fn parse_file(contents: &str) -> Result<Time, Box<dyn Error>> {
+    let t = timetxt::parse_time(contents)?;
+    match file_zone(contents)? {
+        Some(zone) => shift(&t, |time| {
+            zone.from_local_datetime(&time)
+                .earliest()
+                .map_or(time, |t| t.with_timezone(&Local).naive_local())
+        }),
+        None => Ok(t),
+    }
+}
+
+/// The zone of a `// timezone:` comment before the first day
+fn file_zone(contents: &str) -> Result<Option<Tz>, String> {
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() {
+            continue;
+        }
+        let Some(comment) = line.strip_prefix("//") else {
+            break;
+        };
+        if let Some(zone) = comment.trim().strip_prefix("timezone:") {
+            let zone = zone.trim();
+            return zone
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("{zone:?} isn't a time zone like Europe/Berlin or UTC"));
+        }
+    }
+    Ok(None)
+}
+
+/// Every entry with its start and end moved by `to`, so entries can land
+/// on another day. Markers stay on their day
+fn shift(t: &Time, to: impl Fn(NaiveDateTime) -> NaiveDateTime) -> Result<Time, Box<dyn Error>> {
+    let mut shifted = Time::new();
+    for date in t.days() {
+        shifted.set_marker(date, t.marker(date));
+    }
+    for (date, e) in t.iter_entries() {
+        let start = to(e.start_datetime());
+        let entry = TimeEntry {
+            date: start.date(),
+            start: start.time(),
+            end: e.end_datetime().map(|end| to(end).time()),
+            description: e.description.clone(),
+            cancelled: e.cancelled,
+        };
+        // A daylight saving change can shrink an entry to nothing
+        shifted
+            .add_entry(entry)
+            .map_err(|err| format!("{date} {e} can't be moved to another time zone: {err}"))?;
+    }
+    shifted.sort();
+    Ok(shifted)
+}
+
+/// Reads every file and merges them into one `Time`
+pub fn read_times(paths: &[PathBuf]) -> Result<Time, Box<dyn Error>> {
+    let mut time = Time::new();
+    for path in paths {
+        time = time.merge(read_time(path)?);
+    }
+    Ok(time)
+}
+
+/// Reads a time.txt file that is about to be edited, a missing file is
+/// treated as empty so the first edit creates it
+pub fn read_for_edit(path: &Path) -> Result<String, Box<dyn Error>> {
+    if is_stdin(path) {
+        let message = "standard input can't be edited, pass a file with --file";
+        return Err(UsageError(message.to_string()).into());
+    }
+
+    match fs::read_to_string(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        result => Ok(result.map_err(|e| FileError::new(path, e))?),
+    }
+}
+
+/// Replaces the contents of a file that was read with `read_for_edit`
+pub fn write_file(path: &Path, contents: &str) -> Result<(), FileError> {
+    timetxt::write_atomic(path, contents, SaveOptions::default())
+        .map_err(|e| FileError::new(path, e))
+}
+
+/// The local date entries without a `--date` are added to
+pub fn today() -> NaiveDate {
+    now().date()
+}
+
+/// The local time to the minute, the precision of a time.txt file
+pub fn now() -> NaiveDateTime {
+    let now = Local::now().naive_local();
+    now.with_second(0)
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(now)
+}
+
+/// `now` in the zone of a file's `// timezone:` comment, the time entries
+/// written into it now start or end at
+pub fn now_for(contents: &str) -> Result<NaiveDateTime, String> {
+    Ok(now_in(file_zone(contents)?))
+}
+
+/// Like `now` in another zone, the local time without one
+pub fn now_in(zone: Option<Tz>) -> NaiveDateTime {
+    zone.map_or_else(now, |zone| local_in(now(), zone))
+}
+
+/// A local date and time as it reads in `zone`. Times skipped by a
+/// daylight saving change are left as they are
+fn local_in(local: NaiveDateTime, zone: Tz) -> NaiveDateTime {
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .map_or(local, |t| t.with_timezone(&zone).naive_local())
+}
+
+/// Hours and minutes, Ex. `07:30`
+pub fn format_duration(d: Duration) -> String {
+    format!("{:02}:{:02}", d.num_hours(), d.num_minutes() % 60)
+}
+
+/// An entry with its duration, open entries count up to `now`
+pub fn format_entry(e: &TimeEntry, now: NaiveDateTime) -> String {
+    let end = e
+        .end
+        .map_or_else(|| "--:--".to_string(), |t| t.format("%H:%M").to_string());
+    let note = if e.cancelled {
+        " (cancelled)"
+    } else if e.is_open() {
+        " (running)"
+    } else {
+        ""
+    };
+    format!(
+        "{} {end}  {:>6}  {}{note}",
+        e.start.format("%H:%M"),
+        format_duration(e.duration_at(now)),
+        e.description
+    )
+}
+
+/// Prints a date with its marker, entries and total, returning the total.
+/// Open entries count up to `now`
+pub fn print_day(global: &Global, t: &Time, date: NaiveDate, now: NaiveDateTime) -> Duration {
+    let color = global.output.color;
+    println!("{}", paint(&global.date(date), Style::Label, color));
+    if let Some(marker) = t.marker(date) {
+        println!("{}", paint(&marker.to_string(), Style::Note, color));
+    }
+
+    let mut entries: Vec<_> = t.entries_for(date).iter().collect();
+    entries.sort_by_key(|e| e.start);
+    for e in &entries {
+        let line = format_entry(e, now);
+        if e.is_open() && !e.cancelled {
+            println!("{}", paint(&line, Style::Note, color));
+        } else {
+            println!("{line}");
+        }
+    }
+
+    let total = entries
+        .iter()
+        .filter(|e| !e.cancelled)
+        .map(|e| e.duration_at(now))
+        .sum();
+    let line = format!("{:<11}  {:>6}", "Total", format_duration(total));
+    println!("{}", paint(&line, Style::Bold, color));
+    total
+}
+
+/// The entries of `dates` as one row each, for the machine readable formats
+pub fn entries_table<I>(global: &Global, t: &Time, dates: I, now: NaiveDateTime) -> Table
+where
+    I: IntoIterator<Item = NaiveDate>,
+{
+    let mut table = Table::new(["Date", "Start", "End", "Duration", "Description", "Status"]);
+    let mut total = Duration::zero();
+    for date in dates {
+        let mut entries: Vec<_> = t.entries_for(date).iter().collect();
+        entries.sort_by_key(|e| e.start);
+        for e in entries {
+            let status = if e.cancelled {
+                "cancelled"
+            } else if e.is_open() {
+                "running"
+            } else {
+                "done"
+            };
+            if !e.cancelled {
+                total += e.duration_at(now);
+            }
+            table.push(vec![
+                Cell::text(global.date(date)),
+                Cell::text(e.start.format("%H:%M").to_string()),
+                Cell::text(
+                    e.end
+                        .map(|t| t.format("%H:%M").to_string())
+                        .unwrap_or_default(),
+                ),
+                Cell::Duration(e.duration_at(now)),
+                Cell::text(e.description.as_str()),
+                Cell::text(status),
+            ]);
+        }
+    }
+
+    let empty = || Cell::text("");
+    table.footer(vec![
+        Cell::text("Total"),
+        empty(),
+        empty(),
+        Cell::Duration(total),
+        empty(),
+        empty(),
+    ]);
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_includes() -> Result<(), Box<dyn Error>> {
+        let dir = env::temp_dir().join(format!("timetxt-include-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let write = |name: &str, contents: &str| fs::write(dir.join(name), contents);
+        write("a.txt", "// include: b.txt\n// include: c.txt\n")?;
+        write("b.txt", "// include: ./d.txt\n")?;
+        write("c.txt", "// include: d.txt\n")?;
+        write("d.txt", "2024-03-04\n")?;
+
+        // d.txt is included twice by different paths but is one file
+        let mut files = Vec::new();
+        expand(&dir.join("a.txt"), &mut files)?;
+        assert_eq!(
+            files,
+            vec![
+                dir.join("a.txt"),
+                dir.join("b.txt"),
+                dir.join("./d.txt"),
+                dir.join("c.txt")
+            ]
+        );
+
+        write("d.txt", "// include: a.txt\n")?;
+        let err = expand(&dir.join("a.txt"), &mut Vec::new()).unwrap_err();
+        assert!(err.to_string().contains("includes itself"), "{}", err);
+        // A file including itself by another path
+        fs::write(
+            dir.join("d.txt"),
+            format!(
+                "// include: ../timetxt-include-{}/d.txt\n",
+                std::process::id()
+            ),
+        )?;
+        assert!(expand(&dir.join("d.txt"), &mut Vec::new()).is_err());
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_zone() -> Result<(), Box<dyn Error>> {
+        let berlin: Tz = "Europe/Berlin".parse()?;
+        assert_eq!(
+            file_zone("// My time\n\n// timezone: Europe/Berlin\n2024-03-04\n")?,
+            Some(berlin)
+        );
+        // Only comments before the first day count
+        assert_eq!(file_zone("2024-03-04\n// timezone: Europe/Berlin\n")?, None);
+        assert!(file_zone("// timezone: Mars/Base\n").is_err());
+
+        let t = parse_file("// timezone: Europe/Berlin\n2024-03-04\n9:00 10:00 Call\n")?;
+        let start = berlin
+            .with_ymd_and_hms(2024, 3, 4, 9, 0, 0)
+            .unwrap()
+            .with_timezone(&Local)
+            .naive_local();
+        let (_, e) = t.iter_entries().next().unwrap();
+        assert_eq!(e.start_datetime(), start);
+        assert_eq!(e.duration(), Duration::hours(1));
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use super::{read_for_edit, write_file};
+use clap::Args;
+use std::error::Error;
+use std::path::Path;
+
+#[derive(Debug, Args)]
+pub struct NoteArgs {
+    /// The text to add
+    #[arg(required = true, num_args = 1..)]
+    text: Vec<String>,
+}
+
+/// Adds to the running entry's description, before its metadata
+pub fn run(file: &Path, args: &NoteArgs) -> Result<(), Box<dyn Error>> {
+    let contents = read_for_edit(file)?;
+    let (contents, entry) = timetxt::amend_open_entry(&contents, |mut e| {
+        e.append_description(&args.text.join(" "));
+        e
+    })?
+    .ok_or("no entry is running")?;
+    write_file(file, &contents)?;
+    println!("{entry}");
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use super::table::{Cell, Table};
+use super::{Global, Inputs};
+use clap::Args;
+use std::error::Error;
+use std::process;
+
+#[derive(Debug, Args)]
+pub struct OverlapsArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// Exit with 1 if any entries overlap, Ex. in a pre-commit hook
+    #[arg(long)]
+    exit_code: bool,
+}
+
+/// Every pair of entries of a day whose times overlap with how much time
+/// is counted twice
+pub fn run(global: &Global, args: &OverlapsArgs) -> Result<(), Box<dyn Error>> {
+    let t = global.read(&args.inputs)?;
+    let overlaps = t.overlaps();
+
+    let mut table = Table::new(["Date", "From", "To", "Duration", "First", "Second"]);
+    for o in &overlaps {
+        table.push(vec![
+            Cell::text(global.date(o.date)),
+            Cell::text(o.start.format("%H:%M").to_string()),
+            Cell::text(o.end.format("%H:%M").to_string()),
+            Cell::Duration(o.duration()),
+            Cell::text(o.first.to_string()),
+            Cell::text(o.second.to_string()),
+        ]);
+    }
+    table.print(global.output)?;
+
+    if args.exit_code && !overlaps.is_empty() {
+        process::exit(1);
+    }
+    Ok(())
+}
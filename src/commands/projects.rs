@@ -0,0 +1,46 @@
+use super::table::{Cell, Table};
+use super::{Global, Inputs};
+use chrono::{Duration, NaiveDate};
+use std::collections::BTreeMap;
+use std::error::Error;
+
+/// Every project in name order with its number of entries, the first and
+/// last day it was seen and its total. Cancelled entries are left out
+pub fn run(global: &Global, inputs: &Inputs) -> Result<(), Box<dyn Error>> {
+    let t = global.read(inputs)?;
+
+    let mut projects: BTreeMap<&str, Project> = BTreeMap::new();
+    for (date, e) in t.iter_entries().filter(|(_, e)| !e.cancelled) {
+        for name in e.projects() {
+            let project = projects.entry(name).or_insert(Project {
+                entries: 0,
+                first: *date,
+                last: *date,
+                total: Duration::zero(),
+            });
+            project.entries += 1;
+            project.first = project.first.min(*date);
+            project.last = project.last.max(*date);
+            project.total += e.duration();
+        }
+    }
+
+    let mut table = Table::new(["Project", "Entries", "First", "Last", "Total"]);
+    for (name, project) in projects {
+        table.push(vec![
+            Cell::text(format!("+{name}")),
+            Cell::Number(project.entries),
+            Cell::text(global.date(project.first)),
+            Cell::text(global.date(project.last)),
+            Cell::Duration(project.total),
+        ]);
+    }
+    table.print(global.output)
+}
+
+struct Project {
+    entries: i64,
+    first: NaiveDate,
+    last: NaiveDate,
+    total: Duration,
+}
@@ -0,0 +1,37 @@
+use super::start::start_entry;
+use super::stop::stop_running;
+use super::{now_for, read_for_edit, write_file, FileError};
+use clap::Args;
+use std::error::Error;
+use std::path::Path;
+
+#[derive(Debug, Args)]
+pub struct PunchArgs {
+    /// What to start on when nothing is running [default: the description
+    /// of the latest entry]
+    description: Vec<String>,
+}
+
+/// Stops the running entry, or starts one when none is running
+pub fn run(file: &Path, args: &PunchArgs) -> Result<(), Box<dyn Error>> {
+    let contents = read_for_edit(file)?;
+    let now = now_for(&contents)?;
+    if let Some((contents, entry)) = stop_running(&contents, now.time())? {
+        write_file(file, &contents)?;
+        println!("Stopped {entry}");
+        return Ok(());
+    }
+
+    let description = if args.description.is_empty() {
+        let t = timetxt::parse_time(&contents).map_err(|e| FileError::new(file, e))?;
+        t.latest_entry()
+            .map(|e| e.description.clone())
+            .ok_or("nothing to continue, pass a description")?
+    } else {
+        args.description.join(" ")
+    };
+    let started = start_entry(&contents, &description, now)?;
+    write_file(file, &started.contents)?;
+    println!("Started {}", started.entry);
+    Ok(())
+}
@@ -0,0 +1,270 @@
+use super::table::{Cell, Table};
+use super::{display_name, read_time, read_times, Global, Inputs, RoundArgs};
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use clap::Args;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::PathBuf;
+use timetxt::Time;
+
+#[derive(Debug, Default, Args)]
+pub struct ReportArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// Total each week, starting on Monday unless --week-start or the
+    /// config says otherwise
+    #[arg(long, group = "grouping")]
+    weekly: bool,
+    /// Total each calendar month
+    #[arg(long, group = "grouping")]
+    monthly: bool,
+    /// Total each quarter of the fiscal year, calendar quarters unless
+    /// the config has a fiscal_year_start
+    #[arg(long, group = "grouping")]
+    quarterly: bool,
+    /// Total each fiscal year, named after the year it starts in
+    #[arg(long, group = "grouping")]
+    yearly: bool,
+    /// Show each file in its own column instead of merging them
+    #[arg(long)]
+    separate: bool,
+    /// Highlight days over or under this much time, Ex. 8h or 7:30.
+    /// Half sick days expect half of it and marked days nothing.
+    /// Defaults to the configured expected_hours
+    #[arg(long, value_name = "DURATION", value_parser = timetxt::parse_duration,
+        conflicts_with_all = ["grouping", "separate"])]
+    target: Option<Duration>,
+    #[command(flatten)]
+    round: RoundArgs,
+}
+
+impl ReportArgs {
+    pub fn inputs(&self) -> &Inputs {
+        &self.inputs
+    }
+}
+
+pub fn run(global: &Global, args: &ReportArgs) -> Result<(), Box<dyn Error>> {
+    let files = args.inputs.resolve(global)?;
+    if args.separate {
+        return run_separate(global, &files, args);
+    }
+
+    let t = global.in_range(global.adjust(read_times(&files)?)?);
+    let rows = rows(global, &t, args);
+    let total = rows.iter().map(|row| row.total).sum();
+    if !is_daily(args) {
+        return print_periods(global, args, rows, total);
+    }
+
+    let mut table = Table::new([period_name(args), "Total", "Marker"]);
+    let target = args.target.or(global.workday);
+    for row in rows {
+        let total = match target {
+            Some(target) => Cell::Target(row.total, t.expected_for(row.start, target)),
+            None => Cell::Duration(row.total),
+        };
+        table.push(vec![Cell::Text(row.label), total, Cell::Text(row.marker)]);
+    }
+    table.footer(vec![
+        Cell::text("Total"),
+        Cell::Duration(total),
+        Cell::text(""),
+    ]);
+    table.print(global.output)
+}
+
+/// Weeks, months, quarters or years with the change from the one before,
+/// empty periods in between count as nothing tracked
+fn print_periods(
+    global: &Global,
+    args: &ReportArgs,
+    rows: Vec<Row>,
+    total: Duration,
+) -> Result<(), Box<dyn Error>> {
+    let mut table = Table::new([period_name(args), "Total", "Change"]);
+    let mut previous: Option<&Row> = None;
+    for row in &rows {
+        let change = match previous {
+            Some(p) if p.end.succ_opt() == Some(row.start) => Cell::Change(row.total - p.total),
+            Some(_) => Cell::Change(row.total),
+            None => Cell::text(""),
+        };
+        table.push(vec![
+            Cell::text(row.label.clone()),
+            Cell::Duration(row.total),
+            change,
+        ]);
+        previous = Some(row);
+    }
+    table.footer(vec![
+        Cell::text("Total"),
+        Cell::Duration(total),
+        Cell::text(""),
+    ]);
+    table.print(global.output)
+}
+
+fn is_daily(args: &ReportArgs) -> bool {
+    !args.weekly && !args.monthly && !args.quarterly && !args.yearly
+}
+
+/// One column per file followed by the combined total
+fn run_separate(
+    global: &Global,
+    files: &[PathBuf],
+    args: &ReportArgs,
+) -> Result<(), Box<dyn Error>> {
+    let times = files
+        .iter()
+        .map(|f| Ok(global.in_range(global.adjust(read_time(f)?)?)))
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+    // Keyed by the first day so custom date formats still sort by date
+    let mut totals: BTreeMap<(NaiveDate, String), Vec<Duration>> = BTreeMap::new();
+    for (i, t) in times.iter().enumerate() {
+        for row in rows(global, t, args) {
+            totals
+                .entry((row.start, row.label))
+                .or_insert_with(|| vec![Duration::zero(); times.len()])[i] = row.total;
+        }
+    }
+
+    let mut columns = vec![period_name(args).to_string()];
+    columns.extend(files.iter().map(|f| display_name(f)));
+    columns.push("Total".to_string());
+    let mut table = Table::new(columns);
+    for ((_, label), totals) in totals {
+        table.push(row(Cell::Text(label), &totals));
+    }
+    let totals: Vec<Duration> = times
+        .iter()
+        .map(|t| rows(global, t, args).iter().map(|row| row.total).sum())
+        .collect();
+    table.footer(row(Cell::text("Total"), &totals));
+    table.print(global.output)
+}
+
+/// A label, one cell per file and their sum
+fn row(label: Cell, totals: &[Duration]) -> Vec<Cell> {
+    let sum: Duration = totals.iter().copied().sum();
+    Some(label)
+        .into_iter()
+        .chain(totals.iter().chain(Some(&sum)).map(|d| Cell::Duration(*d)))
+        .collect()
+}
+
+fn period_name(args: &ReportArgs) -> &'static str {
+    if args.weekly {
+        "Week"
+    } else if args.monthly {
+        "Month"
+    } else if args.quarterly {
+        "Quarter"
+    } else if args.yearly {
+        "Year"
+    } else {
+        "Date"
+    }
+}
+
+/// A day, week or month of the report
+struct Row {
+    /// The day, or the first day of the week, month, quarter or year
+    start: NaiveDate,
+    /// The last day of it
+    end: NaiveDate,
+    label: String,
+    total: Duration,
+    marker: String,
+}
+
+impl Row {
+    fn period<L: ToString>(start: NaiveDate, end: NaiveDate, label: L, total: Duration) -> Row {
+        Row {
+            start,
+            end,
+            label: label.to_string(),
+            total,
+            marker: String::new(),
+        }
+    }
+}
+
+/// The rows of the report, rounded when asked to
+fn rows(global: &Global, t: &Time, args: &ReportArgs) -> Vec<Row> {
+    let mut rows: Vec<Row> = if args.weekly {
+        t.totals_by_week(global.week_start)
+            .into_iter()
+            .map(|(week, total)| Row::period(week.start(), week.end(), week, total))
+            .collect()
+    } else if args.monthly {
+        t.totals_by_month()
+            .into_iter()
+            .map(|(month, total)| Row::period(month.start(), month.end(), month, total))
+            .collect()
+    } else if args.quarterly || args.yearly {
+        let months = if args.quarterly { 3 } else { 12 };
+        let mut totals: BTreeMap<NaiveDate, Duration> = BTreeMap::new();
+        for date in t.days() {
+            let start = fiscal_period(date, global.fiscal_year_start, months);
+            *totals.entry(start).or_insert_with(Duration::zero) += t.total_for(date);
+        }
+        totals
+            .into_iter()
+            .map(|(start, total)| {
+                let end = add_months(start, months).pred_opt().unwrap_or(start);
+                let label = fiscal_label(start, global.fiscal_year_start, months);
+                Row::period(start, end, label, total)
+            })
+            .collect()
+    } else {
+        t.days()
+            .map(|date| Row {
+                start: date,
+                end: date,
+                label: global.date(date),
+                total: t.total_for(date),
+                marker: t.marker(date).map(|m| m.to_string()).unwrap_or_default(),
+            })
+            .collect()
+    };
+    for row in &mut rows {
+        row.total = args.round.apply(global, row.total);
+    }
+
+    rows
+}
+
+/// The first day of the quarter or year `date` is in, counted from
+/// `fiscal_start`, the month fiscal years start in
+fn fiscal_period(date: NaiveDate, fiscal_start: u32, months: u32) -> NaiveDate {
+    // Months since the start of the fiscal year the date is in
+    let into_year = (date.month() + 12 - fiscal_start) % 12;
+    let into_period = into_year % months;
+    let first = NaiveDate::from_ymd_opt(date.year(), date.month(), 1).unwrap_or(date);
+    subtract_months(first, into_period)
+}
+
+/// `2024-Q1` or `2024` for calendar years, `FY2024-Q1` or `FY2024` for
+/// fiscal years named after the year they start in
+fn fiscal_label(start: NaiveDate, fiscal_start: u32, months: u32) -> String {
+    let into_year = (start.month() + 12 - fiscal_start) % 12;
+    let year_start = subtract_months(start, into_year);
+    let prefix = if fiscal_start == 1 { "" } else { "FY" };
+    if months == 12 {
+        format!("{prefix}{}", year_start.year())
+    } else {
+        format!("{prefix}{}-Q{}", year_start.year(), into_year / 3 + 1)
+    }
+}
+
+fn add_months(date: NaiveDate, months: u32) -> NaiveDate {
+    date.checked_add_months(Months::new(months))
+        .unwrap_or(NaiveDate::MAX)
+}
+
+fn subtract_months(date: NaiveDate, months: u32) -> NaiveDate {
+    date.checked_sub_months(Months::new(months))
+        .unwrap_or(NaiveDate::MIN)
+}
@@ -0,0 +1,19 @@
+use super::start::start_entry;
+use super::{now_for, read_for_edit, write_file, FileError};
+use std::error::Error;
+use std::path::Path;
+
+/// Starts a new entry with the description, tags included, of the latest one
+pub fn run(file: &Path) -> Result<(), Box<dyn Error>> {
+    let contents = read_for_edit(file)?;
+    let t = timetxt::parse_time(&contents).map_err(|e| FileError::new(file, e))?;
+    let last = t.latest_entry().ok_or("there is no entry to continue")?;
+    if last.is_open() && !last.cancelled {
+        return Err(format!("{} is still running", last.description).into());
+    }
+
+    let started = start_entry(&contents, &last.description, now_for(&contents)?)?;
+    write_file(file, &started.contents)?;
+    println!("Started {}", started.entry);
+    Ok(())
+}
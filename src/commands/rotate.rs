@@ -0,0 +1,78 @@
+use super::archive::append_days;
+use super::{includes, read_for_edit, today, write_file, FileError};
+use chrono::NaiveDate;
+use clap::Args;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Args)]
+pub struct RotateArgs {
+    /// One file per month, Ex. time-2024-03.txt, instead of one per year
+    #[arg(long)]
+    monthly: bool,
+}
+
+/// Moves the days before this year, or this month with `--monthly`, into
+/// one file per period next to the file, Ex. `time-2023.txt`. The files
+/// are listed in `// include:` comments at the top so reports on the file
+/// still cover every day
+pub fn run(file: &Path, args: &RotateArgs) -> Result<(), Box<dyn Error>> {
+    let contents = read_for_edit(file)?;
+    // Only whole days are moved, a file that doesn't parse could lose lines
+    let t = timetxt::parse_time(&contents).map_err(|e| FileError::new(file, e))?;
+    let format = if args.monthly { "%Y-%m" } else { "%Y" };
+    let period = |date: NaiveDate| date.format(format).to_string();
+    let current = period(today());
+
+    let periods: BTreeSet<String> = t.days().map(period).filter(|p| *p < current).collect();
+    if periods.is_empty() {
+        println!("No days before {current}");
+        return Ok(());
+    }
+
+    // The rotated files are written first so a failure can't lose any days
+    let mut names = Vec::new();
+    for p in &periods {
+        let path = rotated_path(file, p);
+        let (_, days) = timetxt::take_days(&contents, |d| period(d) == *p);
+        append_days(&path, &days)?;
+
+        let count = t.days().filter(|d| period(*d) == *p).count();
+        let days = if count == 1 { "day" } else { "days" };
+        println!("Moved {count} {days} to {}", path.display());
+        names.extend(path.file_name().map(|n| n.to_string_lossy().into_owned()));
+    }
+    let (kept, _) = timetxt::take_days(&contents, |d| period(d) < current);
+    write_file(file, &with_includes(&kept, &names))?;
+    Ok(())
+}
+
+/// `time.txt` is rotated to `time-2023.txt` in the same directory
+fn rotated_path(file: &Path, period: &str) -> PathBuf {
+    let stem = file
+        .file_stem()
+        .map_or_else(|| "time".into(), |s| s.to_string_lossy());
+    file.with_file_name(format!("{stem}-{period}.txt"))
+}
+
+/// Adds an include comment for each name the contents don't include yet,
+/// after the last one already there or at the top
+fn with_includes(contents: &str, names: &[String]) -> String {
+    let included: Vec<&str> = includes(contents).collect();
+    let missing: String = names
+        .iter()
+        .filter(|n| !included.contains(&n.as_str()))
+        .map(|n| format!("// include: {n}\n"))
+        .collect();
+
+    let lines: Vec<&str> = contents.split_inclusive('\n').collect();
+    let at = lines
+        .iter()
+        .rposition(|line| includes(line).next().is_some())
+        .map_or(0, |i| i + 1);
+    let mut out = lines[..at].concat();
+    out.push_str(&missing);
+    out.push_str(&lines[at..].concat());
+    out
+}
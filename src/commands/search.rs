@@ -0,0 +1,29 @@
+use super::{entries_table, Global, Inputs, UsageError};
+use clap::Args;
+use regex::Regex;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// Text to look for in descriptions, ignoring case
+    pattern: String,
+    /// Treat the pattern as a regular expression, Ex. `review|standup`.
+    /// Add `(?i)` to ignore case
+    #[arg(long, short = 'e')]
+    regex: bool,
+    #[command(flatten)]
+    inputs: Inputs,
+}
+
+/// Prints the matching entries with the total of the ones not cancelled
+pub fn run(global: &Global, args: &SearchArgs) -> Result<(), Box<dyn Error>> {
+    let t = global.read(&args.inputs)?;
+    let matches = if args.regex {
+        let regex = Regex::new(&args.pattern).map_err(|e| UsageError(e.to_string()))?;
+        t.filter_description(&regex)
+    } else {
+        t.filter_description(args.pattern.as_str())
+    };
+
+    entries_table(global, &matches, matches.days(), global.now()).print(global.output)
+}
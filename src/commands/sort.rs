@@ -0,0 +1,8 @@
+use super::fmt::{rewrite, FmtArgs};
+use super::Global;
+use std::error::Error;
+
+/// Sorts each file in place like `fmt` without touching the lines
+pub fn run(global: &Global, args: &FmtArgs) -> Result<(), Box<dyn Error>> {
+    rewrite(global, args, timetxt::sort_time_txt, "sorting")
+}
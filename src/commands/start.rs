@@ -0,0 +1,114 @@
+use super::stop::stop_running;
+use super::{now_for, read_for_edit, write_file};
+use chrono::NaiveDateTime;
+use clap::Args;
+use std::error::Error;
+use std::path::Path;
+use timetxt::TimeEntry;
+
+#[derive(Debug, Args)]
+pub struct StartArgs {
+    /// What the time is being spent on
+    #[arg(required = true, num_args = 1..)]
+    description: Vec<String>,
+}
+
+pub fn run(file: &Path, args: StartArgs) -> Result<(), Box<dyn Error>> {
+    let contents = read_for_edit(file)?;
+    let started = start_entry(&contents, &args.description.join(" "), now_for(&contents)?)?;
+    write_file(file, &started.contents)?;
+
+    if let Some(entry) = started.stopped {
+        println!("Stopped {entry}");
+    }
+    println!("Started {}", started.entry);
+    Ok(())
+}
+
+/// The contents with a new open entry and the entry it stopped, if any
+pub struct Started {
+    pub contents: String,
+    pub entry: TimeEntry,
+    pub stopped: Option<TimeEntry>,
+}
+
+/// Adds an open entry at `now`. Only one timer runs at a time, starting
+/// a new one stops the last
+pub fn start_entry(
+    contents: &str,
+    description: &str,
+    now: NaiveDateTime,
+) -> Result<Started, Box<dyn Error>> {
+    let (contents, stopped) = match stop_running(contents, now.time())? {
+        Some((contents, entry)) => (contents, Some(entry)),
+        None => (contents.to_string(), None),
+    };
+
+    let entry = TimeEntry::builder()
+        .date(now.date())
+        .start(now.time())?
+        .description(description)
+        .build()?;
+    Ok(Started {
+        contents: timetxt::insert_entry(&contents, &entry),
+        entry,
+        stopped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::stop::stop_running;
+    use chrono::{NaiveDate, NaiveTime};
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(1822, 1, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_start_entry() -> Result<(), Box<dyn Error>> {
+        let input = "// My time\r\n\
+        1822-01-15\r\n\
+        3:00 4:00 Sketched ideas\r\n\
+        x 5:00 --:-- Cancelled\r\n";
+
+        let started = start_entry(input, "Built a model", at(15, 9, 0))?;
+        assert!(started.stopped.is_none());
+        assert_eq!(
+            started.contents,
+            "// My time\r\n\
+            1822-01-15\r\n\
+            3:00 4:00 Sketched ideas\r\n\
+            x 5:00 --:-- Cancelled\r\n\
+            09:00 --:-- Built a model\r\n"
+        );
+
+        // Starting the next day stops the running entry past midnight
+        let next = start_entry(&started.contents, "Rested", at(16, 1, 30))?;
+        assert_eq!(
+            next.stopped.map(|e| e.to_string()),
+            Some("09:00 01:30 Built a model".to_string())
+        );
+        assert!(next
+            .contents
+            .ends_with("09:00 01:30 Built a model\r\n\r\n1822-01-16\r\n01:30 --:-- Rested\r\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_stop_running() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-15\n9:00 --:-- Built a model\n";
+        let nine = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        assert!(stop_running(input, nine).is_err());
+
+        let (out, stopped) = stop_running(input, nine + chrono::Duration::minutes(30))?.unwrap();
+        assert_eq!(stopped.to_string(), "09:00 09:30 Built a model");
+        assert_eq!(out, "1822-01-15\n9:00 09:30 Built a model\n");
+        assert!(stop_running(&out, nine)?.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use super::table::{Cell, Table};
+use super::{format_duration, Global, Inputs};
+use std::error::Error;
+use timetxt::TimeEntry;
+
+/// Totals, averages and records for the days in range. Values are text so
+/// they line up on the left whatever they hold
+pub fn run(global: &Global, inputs: &Inputs) -> Result<(), Box<dyn Error>> {
+    let t = global.read(inputs)?;
+    let stats = t.stats();
+
+    let entry = |e: Option<&TimeEntry>| {
+        e.map_or_else(String::new, |e| {
+            let date = global.date(e.date);
+            format!(
+                "{}  {date} {}",
+                format_duration(e.duration()),
+                e.description
+            )
+        })
+    };
+    let count = |n: usize| Cell::Text(n.to_string());
+    let duration = |d| Cell::Text(format_duration(d));
+
+    let mut table = Table::new(["Stat", "Value"]);
+    table.push(vec![Cell::text("Total"), duration(stats.total)]);
+    table.push(vec![Cell::text("Days tracked"), count(stats.days_tracked)]);
+    table.push(vec![Cell::text("Working days"), count(stats.working_days)]);
+    table.push(vec![
+        Cell::text("Average working day"),
+        duration(stats.average_working_day),
+    ]);
+    table.push(vec![
+        Cell::text("Longest day"),
+        Cell::Text(stats.busiest_day.map_or_else(String::new, |(date, total)| {
+            format!("{}  {}", format_duration(total), global.date(date))
+        })),
+    ]);
+    table.push(vec![
+        Cell::text("Longest entry"),
+        Cell::Text(entry(stats.longest_entry)),
+    ]);
+    table.push(vec![Cell::text("Entries"), count(stats.entries)]);
+    table.push(vec![Cell::text("Projects"), count(stats.projects)]);
+    table.push(vec![Cell::text("Tags"), count(stats.tags)]);
+    table.push(vec![
+        Cell::text("Streak"),
+        count(t.streak(global.now().date())),
+    ]);
+    table.print(global.output)
+}
@@ -0,0 +1,45 @@
+use super::table::{Cell, Format, Table};
+use super::{format_duration, Global, Inputs};
+use chrono::Duration;
+use std::error::Error;
+
+/// One line for a shell prompt, Ex. `Fix login +web 01:25 (today 05:40)`,
+/// or `today 05:40` when nothing is running. Other formats get a row with
+/// the running entry's description and times in minutes
+pub fn run(global: &Global, inputs: &Inputs) -> Result<(), Box<dyn Error>> {
+    let t = global.read(inputs)?;
+    let now = global.now();
+
+    let running = t
+        .iter_entries()
+        .map(|(_, e)| e)
+        .filter(|e| e.is_open() && !e.cancelled)
+        .max_by_key(|e| e.start_datetime());
+    let today: Duration = t
+        .entries_for(now.date())
+        .iter()
+        .filter(|e| !e.cancelled)
+        .map(|e| e.duration_at(now))
+        .sum();
+
+    if global.output.format != Format::Table {
+        let mut table = Table::new(["Description", "Running", "Today"]);
+        table.push(vec![
+            Cell::text(running.map(|e| e.description.clone()).unwrap_or_default()),
+            running.map_or_else(|| Cell::text(""), |e| Cell::Duration(e.duration_at(now))),
+            Cell::Duration(today),
+        ]);
+        return table.print(global.output);
+    }
+
+    match running {
+        Some(e) => println!(
+            "{} {} (today {})",
+            e.description,
+            format_duration(e.duration_at(now)),
+            format_duration(today)
+        ),
+        None => println!("today {}", format_duration(today)),
+    }
+    Ok(())
+}
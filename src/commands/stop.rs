@@ -0,0 +1,30 @@
+use super::{now_for, read_for_edit, write_file};
+use chrono::NaiveTime;
+use std::error::Error;
+use std::path::Path;
+use timetxt::TimeEntry;
+
+pub fn run(file: &Path) -> Result<(), Box<dyn Error>> {
+    let contents = read_for_edit(file)?;
+    let Some((contents, entry)) = stop_running(&contents, now_for(&contents)?.time())? else {
+        return Err("no entry is running".into());
+    };
+
+    write_file(file, &contents)?;
+    println!("Stopped {entry}");
+    Ok(())
+}
+
+/// Closes the running entry at `end`, refusing to leave an entry that
+/// starts and ends in the same minute behind
+pub fn stop_running(
+    contents: &str,
+    end: NaiveTime,
+) -> Result<Option<(String, TimeEntry)>, Box<dyn Error>> {
+    match timetxt::close_open_entry(contents, end) {
+        Some((_, entry)) if entry.end == Some(entry.start) => {
+            Err(format!("{} started less than a minute ago", entry.description).into())
+        }
+        stopped => Ok(stopped),
+    }
+}
@@ -0,0 +1,171 @@
+use super::table::{Cell, Table};
+use super::{Global, Inputs, RoundArgs};
+use chrono::{Datelike, Duration};
+use clap::{Args, ValueEnum};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::convert::TryInto;
+use std::error::Error;
+use timetxt::{DateRange, Time};
+
+#[derive(Debug, Args)]
+pub struct SummaryArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// What to group the tracked time by
+    #[arg(long, alias = "group-by", value_enum, default_value_t = GroupBy::Tag)]
+    by: GroupBy,
+    #[command(flatten)]
+    round: RoundArgs,
+}
+
+impl SummaryArgs {
+    pub fn inputs(&self) -> &Inputs {
+        &self.inputs
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum GroupBy {
+    Tag,
+    Project,
+    Description,
+    /// `client:` metadata or the configured clients of projects
+    Client,
+    /// The day of the week, with the average of each weekday in the range
+    Weekday,
+}
+
+pub fn run(global: &Global, args: &SummaryArgs) -> Result<(), Box<dyn Error>> {
+    let t = global.read(&args.inputs)?;
+    let none = || "(none)".to_string();
+    let (group, mut rows): (&str, Vec<(String, Duration)>) = match args.by {
+        GroupBy::Tag => (
+            "Tag",
+            t.totals_by_tag()
+                .into_iter()
+                .map(|(tag, total)| (tag.map_or_else(none, |t| format!("#{t}")), total))
+                .collect(),
+        ),
+        GroupBy::Project => (
+            "Project",
+            t.totals_by_project()
+                .into_iter()
+                .map(|(project, total)| (project.map_or_else(none, |p| format!("+{p}")), total))
+                .collect(),
+        ),
+        GroupBy::Description => (
+            "Description",
+            t.totals_by_description().into_iter().collect(),
+        ),
+        GroupBy::Client => {
+            let mut totals: BTreeMap<Option<String>, Duration> = BTreeMap::new();
+            for (_, e) in t.iter_entries().filter(|(_, e)| !e.cancelled) {
+                *totals
+                    .entry(global.client(e))
+                    .or_insert_with(Duration::zero) += e.duration();
+            }
+            let rows = totals
+                .into_iter()
+                .map(|(client, total)| (client.unwrap_or_else(none), total))
+                .collect();
+            ("Client", rows)
+        }
+        // Weekdays have columns of their own
+        GroupBy::Weekday => return run_weekdays(global, &t, args),
+    };
+    // Longest first, ties in name order
+    rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    // Entries with several tags count once per tag, so percentages are of
+    // the tracked time and can add up to more than 100
+    let total = args.round.apply(global, t.total());
+    let mut columns = vec![group, "Total", "Share"];
+    if global.hourly_rate.is_some() {
+        columns.push("Amount");
+    }
+    let amount = |d: Duration| {
+        global
+            .hourly_rate
+            .map(|rate| Cell::Amount(rate * d.num_minutes() as f64 / 60.0))
+    };
+
+    let mut table = Table::new(columns);
+    for (name, duration) in rows {
+        let duration = args.round.apply(global, duration);
+        let mut row = vec![
+            Cell::Text(name),
+            Cell::Duration(duration),
+            Cell::Percent(percent(duration, total)),
+        ];
+        row.extend(amount(duration));
+        table.push(row);
+    }
+    let mut footer = vec![Cell::text("Total"), Cell::Duration(total), Cell::text("")];
+    footer.extend(amount(total));
+    table.footer(footer);
+    table.print(global.output)
+}
+
+/// A row per day of the week from the week start. Days counts every such
+/// day from the first tracked day to the last, or across the range when
+/// one is given, leaving out days with a marker. Untracked days bring the
+/// average down, which is the point
+fn run_weekdays(global: &Global, t: &Time, args: &SummaryArgs) -> Result<(), Box<dyn Error>> {
+    let totals = t.totals_by_weekday();
+    let span = match (t.days().next(), t.days().last()) {
+        (Some(first), Some(last)) => {
+            let (first, last) = match global.range {
+                Some(range) => (range.start().max(first), range.end().min(last)),
+                None => (first, last),
+            };
+            DateRange::new(first, last).iter_days().collect()
+        }
+        _ => Vec::new(),
+    };
+
+    let total = args.round.apply(global, t.total());
+    let mut table = Table::new(["Weekday", "Days", "Total", "Average", "Share"]);
+    let mut weekday = global.week_start;
+    for _ in 0..7 {
+        let days = span
+            .iter()
+            .filter(|d| d.weekday() == weekday && t.marker(**d).is_none())
+            .count();
+        let tracked = totals.get(&weekday).copied().unwrap_or_else(Duration::zero);
+        let tracked = args.round.apply(global, tracked);
+        let average = match i32::try_from(days) {
+            Ok(days) if days > 0 => tracked / days,
+            _ => Duration::zero(),
+        };
+        table.push(vec![
+            Cell::text(weekday.to_string()),
+            Cell::Number(i64::try_from(days)?),
+            Cell::Duration(tracked),
+            Cell::Duration(average),
+            Cell::Percent(percent(tracked, total)),
+        ]);
+        weekday = weekday.succ();
+    }
+    table.footer(vec![
+        Cell::text("Total"),
+        Cell::Number(
+            span.iter()
+                .filter(|d| t.marker(**d).is_none())
+                .count()
+                .try_into()?,
+        ),
+        Cell::Duration(total),
+        Cell::text(""),
+        Cell::text(""),
+    ]);
+    table.print(global.output)
+}
+
+fn percent(part: Duration, total: Duration) -> i64 {
+    if total > Duration::zero() {
+        (part.num_minutes() * 100 + total.num_minutes() / 2) / total.num_minutes()
+    } else {
+        0
+    }
+}
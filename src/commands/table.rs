@@ -0,0 +1,336 @@
+//! Rows printed by the reporting commands, in whichever `--format` was picked
+//!
+//! `--porcelain` is the format scripts should parse. Its first line holds
+//! the column keys, the ones JSON uses, and each following line a row,
+//! with fields separated by tabs. Durations are whole minutes, hours and
+//! money plain decimals with a `.` and dates `YYYY-MM-DD` whatever the
+//! configured date_format. Tabs and newlines inside fields become
+//! spaces. There are no total rows, colors or headings. New columns are
+//! only ever added at the end, so read fields by key or by position
+
+use super::format_duration;
+use chrono::Duration;
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+use std::error::Error;
+use std::io;
+
+/// How reports are printed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Aligned columns with a header, for reading
+    #[default]
+    Table,
+    /// Tab separated values without a header, for `cut` and `awk`
+    Plain,
+    /// Comma separated values with a header, durations in minutes
+    Csv,
+    /// An array of objects keyed by column, durations in minutes
+    Json,
+    /// Tab separated values with a header of column keys, durations in
+    /// minutes. Stable between versions, see `--porcelain`
+    Porcelain,
+}
+
+/// The format and whether to color it, colors only apply to tables
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Output {
+    pub format: Format,
+    pub color: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Bold,
+    /// Dates and other row labels
+    Label,
+    /// Time that met its target
+    Good,
+    /// Time short of its target
+    Bad,
+    /// Running entries and other things to notice
+    Note,
+}
+
+/// Wraps `text` in ANSI escape codes when `color` is set
+pub fn paint(text: &str, style: Style, color: bool) -> String {
+    if !color || text.is_empty() {
+        return text.to_string();
+    }
+    let code = match style {
+        Style::Bold => "1",
+        Style::Label => "36",
+        Style::Good => "32",
+        Style::Bad => "31",
+        Style::Note => "33",
+    };
+    format!("\x1b[{code}m{text}\x1b[0m")
+}
+
+#[derive(Debug, Clone)]
+pub enum Cell {
+    Text(String),
+    Duration(Duration),
+    /// The difference to an earlier duration, with its sign
+    Change(Duration),
+    /// A duration and the time expected for it, colored by whether it met it
+    Target(Duration, Duration),
+    Percent(i64),
+    /// A duration as decimal hours, Ex. 1.25 for billing
+    Hours(Duration),
+    /// A count, Ex. of entries
+    Number(i64),
+    /// Money, printed with two decimals
+    Amount(f64),
+    /// Money printed with a currency, numbers only in CSV and JSON
+    Money(f64, Currency),
+}
+
+/// How `Cell::Money` prints amounts, Ex. `$1,234.50` or `1.234,50 €`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Currency {
+    pub symbol: String,
+    /// Puts the symbol after the amount with a space between them
+    pub symbol_after: bool,
+    pub decimal_separator: String,
+    /// Between every three digits, none by default
+    pub thousands_separator: String,
+}
+
+impl Default for Currency {
+    fn default() -> Currency {
+        Currency {
+            symbol: String::new(),
+            symbol_after: false,
+            decimal_separator: ".".to_string(),
+            thousands_separator: String::new(),
+        }
+    }
+}
+
+impl Currency {
+    pub fn format(&self, amount: f64) -> String {
+        let cents = (amount.abs() * 100.0).round() as u64;
+        let digits = (cents / 100).to_string();
+        let mut whole = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                whole.push_str(&self.thousands_separator);
+            }
+            whole.push(digit);
+        }
+        let sign = if amount < 0.0 && cents > 0 { "-" } else { "" };
+        let number = format!("{sign}{whole}{}{:02}", self.decimal_separator, cents % 100);
+        if self.symbol.is_empty() {
+            number
+        } else if self.symbol_after {
+            format!("{number} {}", self.symbol)
+        } else {
+            format!("{}{number}", self.symbol)
+        }
+    }
+}
+
+impl Cell {
+    pub fn text<S: Into<String>>(s: S) -> Cell {
+        Cell::Text(s.into())
+    }
+
+    fn human(&self) -> String {
+        match *self {
+            Cell::Text(ref s) => s.clone(),
+            Cell::Duration(d) | Cell::Target(d, _) => format_duration(d),
+            Cell::Change(d) if d < Duration::zero() => format!("-{}", format_duration(-d)),
+            Cell::Change(d) => format!("+{}", format_duration(d)),
+            Cell::Percent(p) => format!("{p}%"),
+            Cell::Number(n) => n.to_string(),
+            Cell::Hours(d) => format!("{:.2}", hours(d)),
+            Cell::Amount(a) => format!("{a:.2}"),
+            Cell::Money(a, ref currency) => currency.format(a),
+        }
+    }
+
+    fn style(&self) -> Option<Style> {
+        match *self {
+            Cell::Target(d, expected) if d >= expected && d > Duration::zero() => Some(Style::Good),
+            Cell::Target(d, expected) if d < expected => Some(Style::Bad),
+            _ => None,
+        }
+    }
+
+    fn machine(&self) -> Value {
+        match *self {
+            Cell::Text(ref s) => Value::from(s.as_str()),
+            Cell::Duration(d) | Cell::Change(d) | Cell::Target(d, _) => {
+                Value::from(d.num_minutes())
+            }
+            Cell::Percent(p) | Cell::Number(p) => Value::from(p),
+            Cell::Hours(d) => Value::from((hours(d) * 100.0).round() / 100.0),
+            Cell::Amount(a) | Cell::Money(a, _) => Value::from((a * 100.0).round() / 100.0),
+        }
+    }
+
+    fn right_aligned(&self) -> bool {
+        !matches!(*self, Cell::Text(_))
+    }
+}
+
+/// Named columns, rows and optional total rows. The total rows are left
+/// out of CSV and JSON where they can be summed from the rows
+#[derive(Debug, Clone)]
+pub struct Table {
+    columns: Vec<String>,
+    rows: Vec<Vec<Cell>>,
+    footer: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    pub fn new<I, S>(columns: I) -> Table
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Table {
+            columns: columns.into_iter().map(Into::into).collect(),
+            rows: Vec::new(),
+            footer: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<Cell>) {
+        self.rows.push(row);
+    }
+
+    /// Adds a total row, printed bold after the rows
+    pub fn footer(&mut self, row: Vec<Cell>) {
+        self.footer.push(row);
+    }
+
+    pub fn print(&self, output: Output) -> Result<(), Box<dyn Error>> {
+        match output.format {
+            Format::Table => self.print_table(output.color),
+            Format::Plain => {
+                for row in self.rows.iter().chain(&self.footer) {
+                    let cells: Vec<String> = row.iter().map(Cell::human).collect();
+                    println!("{}", cells.join("\t"));
+                }
+            }
+            Format::Csv => {
+                let mut w = csv::Writer::from_writer(io::stdout());
+                w.write_record(&self.columns)?;
+                for row in &self.rows {
+                    w.write_record(row.iter().map(|c| match c.machine() {
+                        Value::String(s) => s,
+                        v => v.to_string(),
+                    }))?;
+                }
+                w.flush()?;
+            }
+            Format::Porcelain => {
+                let keys: Vec<String> = self.columns.iter().map(|c| json_key(c)).collect();
+                println!("{}", keys.join("\t"));
+                for row in &self.rows {
+                    let fields: Vec<String> = row
+                        .iter()
+                        .map(|c| match c.machine() {
+                            Value::String(s) => s.replace(['\t', '\n', '\r'], " "),
+                            v => v.to_string(),
+                        })
+                        .collect();
+                    println!("{}", fields.join("\t"));
+                }
+            }
+            Format::Json => {
+                let rows: Vec<Value> = self
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        let object: Map<String, Value> = self
+                            .columns
+                            .iter()
+                            .map(|c| json_key(c))
+                            .zip(row.iter().map(Cell::machine))
+                            .collect();
+                        Value::Object(object)
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&rows)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn print_table(&self, color: bool) {
+        let mut widths: Vec<usize> = self.columns.iter().map(|c| c.chars().count()).collect();
+        for row in self.rows.iter().chain(&self.footer) {
+            for (i, cell) in row.iter().enumerate() {
+                widths[i] = widths[i].max(cell.human().chars().count());
+            }
+        }
+        // Numbers line up on the right, headers follow their column
+        let right: Vec<bool> = (0..self.columns.len())
+            .map(|i| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(i))
+                    .any(Cell::right_aligned)
+            })
+            .collect();
+
+        // Pads before painting so escape codes don't count towards widths
+        // and the last column isn't padded so lines end without spaces
+        let last = self.columns.len() - 1;
+        let pad = |i: usize, text: &str| {
+            let width = widths[i];
+            if right[i] {
+                format!("{text:>width$}")
+            } else if i == last {
+                text.to_string()
+            } else {
+                format!("{text:<width$}")
+            }
+        };
+        let print = |cells: Vec<String>| println!("{}", cells.join("  ").trim_end());
+
+        print(
+            self.columns
+                .iter()
+                .enumerate()
+                .map(|(i, c)| paint(&pad(i, c), Style::Bold, color))
+                .collect(),
+        );
+        for row in &self.rows {
+            print(
+                row.iter()
+                    .enumerate()
+                    .map(|(i, cell)| {
+                        let text = pad(i, &cell.human());
+                        match cell.style() {
+                            Some(style) => paint(&text, style, color),
+                            None if i == 0 => paint(&text, Style::Label, color),
+                            None => text,
+                        }
+                    })
+                    .collect(),
+            );
+        }
+        for footer in &self.footer {
+            print(
+                footer
+                    .iter()
+                    .enumerate()
+                    .map(|(i, cell)| paint(&pad(i, &cell.human()), Style::Bold, color))
+                    .collect(),
+            );
+        }
+    }
+}
+
+fn hours(d: Duration) -> f64 {
+    d.num_minutes() as f64 / 60.0
+}
+
+/// `Total` becomes `total`, `Start time` becomes `start_time`
+fn json_key(column: &str) -> String {
+    column.to_lowercase().replace(' ', "_")
+}
@@ -0,0 +1,30 @@
+use super::table::{Cell, Table};
+use super::{Global, Inputs};
+use chrono::Duration;
+use std::collections::BTreeMap;
+use std::error::Error;
+
+/// Every tag in name order with how many entries have it and their total.
+/// Cancelled entries are left out
+pub fn run(global: &Global, inputs: &Inputs) -> Result<(), Box<dyn Error>> {
+    let t = global.read(inputs)?;
+
+    let mut tags: BTreeMap<&str, (i64, Duration)> = BTreeMap::new();
+    for (_, e) in t.iter_entries().filter(|(_, e)| !e.cancelled) {
+        for tag in e.tags() {
+            let (count, total) = tags.entry(tag).or_insert((0, Duration::zero()));
+            *count += 1;
+            *total += e.duration();
+        }
+    }
+
+    let mut table = Table::new(["Tag", "Entries", "Total"]);
+    for (tag, (count, total)) in tags {
+        table.push(vec![
+            Cell::text(format!("#{tag}")),
+            Cell::Number(count),
+            Cell::Duration(total),
+        ]);
+    }
+    table.print(global.output)
+}
@@ -0,0 +1,98 @@
+use super::table::{Cell, Table};
+use super::{read_times, Global, Inputs, RoundArgs};
+use chrono::{Duration, NaiveDate};
+use clap::Args;
+use std::collections::BTreeMap;
+use std::error::Error;
+use timetxt::Month;
+
+#[derive(Debug, Args)]
+pub struct TimesheetArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// The month to fill in, Ex. 2024-03 [default: this month]
+    #[arg(long, value_name = "YYYY-MM", value_parser = parse_month)]
+    month: Option<Month>,
+    #[command(flatten)]
+    round: RoundArgs,
+}
+
+/// One row per day of the month and one column per project in decimal
+/// hours, with totals for both. Entries count towards their first project
+/// so the totals add up, those without one go under `(none)`
+pub fn run(global: &Global, args: &TimesheetArgs) -> Result<(), Box<dyn Error>> {
+    let month = args.month.unwrap_or_else(|| Month::of(global.now().date()));
+    let t = global
+        .adjust(read_times(&args.inputs.resolve(global)?)?)?
+        .range(month);
+
+    let mut cells: BTreeMap<(Option<String>, NaiveDate), Duration> = BTreeMap::new();
+    for (date, e) in t.iter_entries().filter(|(_, e)| !e.cancelled) {
+        let project = e.projects().first().map(|p| format!("+{p}"));
+        *cells.entry((project, *date)).or_insert_with(Duration::zero) += e.duration();
+    }
+    for hours in cells.values_mut() {
+        *hours = args.round.apply(global, *hours);
+    }
+    // Named projects first, in name order
+    let mut projects: Vec<Option<String>> = cells.keys().map(|(p, _)| p.clone()).collect();
+    projects.dedup();
+    projects.sort_by_key(|p| p.is_none());
+
+    let hours = |d: Duration| {
+        if d > Duration::zero() {
+            Cell::Hours(d)
+        } else {
+            Cell::text("")
+        }
+    };
+    let mut columns = vec!["Date".to_string(), "Day".to_string()];
+    columns.extend(
+        projects
+            .iter()
+            .map(|p| p.clone().unwrap_or_else(|| "(none)".to_string())),
+    );
+    columns.push("Total".to_string());
+
+    let mut table = Table::new(columns);
+    for date in month.iter_days() {
+        let day: Vec<Duration> = projects
+            .iter()
+            .map(|p| {
+                cells
+                    .get(&(p.clone(), date))
+                    .copied()
+                    .unwrap_or_else(Duration::zero)
+            })
+            .collect();
+        let mut row = vec![
+            Cell::text(global.date(date)),
+            Cell::text(date.format("%a").to_string()),
+        ];
+        row.extend(day.iter().map(|d| hours(*d)));
+        row.push(hours(day.iter().copied().sum()));
+        table.push(row);
+    }
+
+    let totals: Vec<Duration> = projects
+        .iter()
+        .map(|p| {
+            cells
+                .iter()
+                .filter(|((project, _), _)| project == p)
+                .map(|(_, d)| *d)
+                .sum()
+        })
+        .collect();
+    let mut footer = vec![Cell::text("Total"), Cell::text("")];
+    footer.extend(totals.iter().map(|d| Cell::Hours(*d)));
+    footer.push(Cell::Hours(totals.iter().copied().sum()));
+    table.footer(footer);
+    table.print(global.output)
+}
+
+fn parse_month(s: &str) -> Result<Month, String> {
+    NaiveDate::parse_from_str(&format!("{s}-01"), "%Y-%m-%d")
+        .map(Month::of)
+        .map_err(|_| format!("{s:?} isn't a month like 2024-03"))
+}
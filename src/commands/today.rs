@@ -0,0 +1,16 @@
+use super::table::Format;
+use super::{entries_table, print_day, Global, Inputs};
+use std::error::Error;
+
+pub fn run(global: &Global, inputs: &Inputs) -> Result<(), Box<dyn Error>> {
+    let t = global.read(inputs)?;
+    let now = global.now();
+
+    // The running entry counts towards the total so far
+    if global.output.format == Format::Table {
+        print_day(global, &t, now.date(), now);
+        Ok(())
+    } else {
+        entries_table(global, &t, Some(now.date()), now).print(global.output)
+    }
+}
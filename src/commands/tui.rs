@@ -0,0 +1,289 @@
+//! `timetxt tui`, a terminal interface for browsing weeks and running the
+//! timer. Built with the `tui` feature
+
+use super::start::start_entry;
+use super::stop::stop_running;
+use super::{format_duration, now_for, parse_file, read_for_edit, write_file, Global};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Row, Table};
+use ratatui::{DefaultTerminal, Frame};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use timetxt::{Time, TimeEntry, Week};
+
+const HELP: &str = "↑↓ day  ←→ week  t today  s start  x stop  r reload  q quit";
+
+/// Runs until `q` is pressed, restoring the terminal on the way out
+pub fn run(global: &Global) -> Result<(), Box<dyn Error>> {
+    let mut app = App::new(global)?;
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+struct App<'a> {
+    global: &'a Global,
+    file: PathBuf,
+    time: Time,
+    modified: Option<SystemTime>,
+    selected: NaiveDate,
+    /// The description being typed after `s`
+    input: Option<String>,
+    /// The outcome of the last key, shown instead of the help
+    message: Option<String>,
+    quit: bool,
+}
+
+impl<'a> App<'a> {
+    fn new(global: &'a Global) -> Result<App<'a>, Box<dyn Error>> {
+        let mut app = App {
+            global,
+            file: global.single_file()?,
+            time: Time::new(),
+            modified: None,
+            selected: global.now().date(),
+            input: None,
+            message: None,
+            quit: false,
+        };
+        // Errors before the interface is up are printed like any other
+        app.time = app.read()?;
+        Ok(app)
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), Box<dyn Error>> {
+        while !self.quit {
+            let now = self.global.now();
+            terminal.draw(|frame| self.draw(frame, now))?;
+
+            // Wake up every second so edits made elsewhere and running
+            // entries show up without a key press
+            if event::poll(std::time::Duration::from_secs(1))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.key(key);
+                    }
+                }
+            } else if self.file_modified() != self.modified {
+                self.reload();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn key(&mut self, key: KeyEvent) {
+        if let Some(input) = &mut self.input {
+            match key.code {
+                KeyCode::Enter => {
+                    let description = input.trim().to_string();
+                    self.input = None;
+                    if !description.is_empty() {
+                        self.start(&description);
+                    }
+                }
+                KeyCode::Esc => self.input = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+            return;
+        }
+
+        self.message = None;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.selected -= Duration::days(1),
+            KeyCode::Down | KeyCode::Char('j') => self.selected += Duration::days(1),
+            KeyCode::Left | KeyCode::Char('h') => self.selected -= Duration::days(7),
+            KeyCode::Right | KeyCode::Char('l') => self.selected += Duration::days(7),
+            KeyCode::Char('t') => self.selected = self.global.now().date(),
+            KeyCode::Char('s') => self.input = Some(String::new()),
+            KeyCode::Char('x') => self.stop(),
+            KeyCode::Char('r') => self.reload(),
+            _ => {}
+        }
+    }
+
+    fn read(&mut self) -> Result<Time, Box<dyn Error>> {
+        self.modified = self.file_modified();
+        let contents = read_for_edit(&self.file)?;
+        self.global.adjust(parse_file(&contents)?)
+    }
+
+    /// Parse errors are shown and the last good contents kept, the file
+    /// may be saved halfway through an edit
+    fn reload(&mut self) {
+        match self.read() {
+            Ok(time) => self.time = time,
+            Err(e) => self.message = Some(format!("error: {e}")),
+        }
+    }
+
+    fn file_modified(&self) -> Option<SystemTime> {
+        fs::metadata(&self.file).and_then(|m| m.modified()).ok()
+    }
+
+    fn start(&mut self, description: &str) {
+        let result = read_for_edit(&self.file)
+            .and_then(|contents| start_entry(&contents, description, now_for(&contents)?))
+            .and_then(|started| {
+                write_file(&self.file, &started.contents)?;
+                Ok(started.entry)
+            });
+        self.finish(result.map(|entry| format!("Started {entry}")));
+    }
+
+    fn stop(&mut self) {
+        let result = read_for_edit(&self.file).and_then(|contents| {
+            let (contents, entry) = stop_running(&contents, now_for(&contents)?.time())?
+                .ok_or("no entry is running")?;
+            write_file(&self.file, &contents)?;
+            Ok(format!("Stopped {entry}"))
+        });
+        self.finish(result);
+    }
+
+    fn finish(&mut self, result: Result<String, Box<dyn Error>>) {
+        match result {
+            Ok(message) => {
+                self.reload();
+                self.message.get_or_insert(message);
+            }
+            Err(e) => self.message = Some(format!("error: {e}")),
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame<'_>, now: NaiveDateTime) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [days, entries] =
+            Layout::horizontal([Constraint::Length(26), Constraint::Min(0)]).areas(body);
+
+        frame.render_widget(Paragraph::new(self.running(now)), header);
+        self.draw_week(frame, days, now);
+        self.draw_day(frame, entries, now);
+
+        let footer_text = match (&self.input, &self.message) {
+            (Some(input), _) => Line::from(format!("Start: {input}_")),
+            (None, Some(message)) => Line::from(message.as_str()),
+            (None, None) => Line::from(HELP).dim(),
+        };
+        frame.render_widget(Paragraph::new(footer_text), footer);
+    }
+
+    fn running(&self, now: NaiveDateTime) -> Line<'_> {
+        let running = self
+            .time
+            .iter_entries()
+            .map(|(_, e)| e)
+            .filter(|e| e.is_open() && !e.cancelled)
+            .max_by_key(|e| (e.date, e.start));
+        match running {
+            Some(e) => Line::from(format!(
+                "● {}  {}",
+                e.description,
+                format_duration(e.duration_at(now))
+            ))
+            .yellow(),
+            None => Line::from("No timer running").dim(),
+        }
+    }
+
+    fn draw_week(&self, frame: &mut Frame<'_>, area: ratatui::layout::Rect, now: NaiveDateTime) {
+        let week = Week::starting_on(self.selected, self.global.week_start);
+        let mut total = Duration::zero();
+        let items: Vec<ListItem<'_>> = week
+            .iter_days()
+            .map(|date| {
+                let day = self.day_total(date, now);
+                total += day;
+                ListItem::new(format!(
+                    "{} {}  {}",
+                    date.format("%a"),
+                    self.global.date(date),
+                    format_duration(day)
+                ))
+            })
+            .collect();
+
+        let selected = week.iter_days().position(|d| d == self.selected);
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("Week {}", format_duration(total))),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(
+            list,
+            area,
+            &mut ListState::default().with_selected(selected),
+        );
+    }
+
+    fn draw_day(&self, frame: &mut Frame<'_>, area: ratatui::layout::Rect, now: NaiveDateTime) {
+        let mut entries: Vec<&TimeEntry> = self.time.entries_for(self.selected).iter().collect();
+        entries.sort_by_key(|e| e.start);
+        let rows = entries.iter().map(|e| {
+            let end = e
+                .end
+                .map_or_else(|| "--:--".to_string(), |t| t.format("%H:%M").to_string());
+            let row = Row::new(vec![
+                e.start.format("%H:%M").to_string(),
+                end,
+                format_duration(e.duration_at(now)),
+                e.description.clone(),
+            ]);
+            if e.cancelled {
+                row.crossed_out().dim()
+            } else if e.is_open() {
+                row.yellow()
+            } else {
+                row
+            }
+        });
+
+        let mut title = format!(
+            "{}  {}",
+            self.global.date(self.selected),
+            format_duration(self.day_total(self.selected, now))
+        );
+        if let Some(marker) = self.time.marker(self.selected) {
+            title.push_str(&format!("  {marker}"));
+        }
+        let widths = [
+            Constraint::Length(5),
+            Constraint::Length(5),
+            Constraint::Length(5),
+            Constraint::Min(0),
+        ];
+        let table = Table::new(rows, widths)
+            .header(Row::new(vec!["Start", "End", "Time", "Description"]).bold())
+            .block(Block::default().borders(Borders::ALL).title(title));
+        frame.render_widget(table, area);
+    }
+
+    /// Like `Time::total_for` with running entries counted up to `now`
+    fn day_total(&self, date: NaiveDate, now: NaiveDateTime) -> Duration {
+        self.time
+            .entries_for(date)
+            .iter()
+            .filter(|e| !e.cancelled)
+            .map(|e| e.duration_at(now))
+            .sum()
+    }
+}
@@ -0,0 +1,22 @@
+use super::{display_name, read_contents, Global, Inputs};
+use std::error::Error;
+use std::process;
+
+/// Prints every problem as `file:line:severity: message` and exits with
+/// status 1 if there were any, so it can run in pre-commit hooks
+pub fn run(global: &Global, inputs: &Inputs) -> Result<(), Box<dyn Error>> {
+    let mut found = false;
+    for file in inputs.resolve(global)? {
+        let contents = read_contents(&file)?;
+        let name = display_name(&file);
+        for d in timetxt::validate(&contents) {
+            println!("{name}:{d}");
+            found = true;
+        }
+    }
+
+    if found {
+        process::exit(1);
+    }
+    Ok(())
+}
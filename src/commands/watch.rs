@@ -0,0 +1,92 @@
+use super::report::ReportArgs;
+use super::summary::SummaryArgs;
+use super::table::{paint, Style};
+use super::week::WeekArgs;
+use super::{is_stdin, now, Global, Inputs, UsageError};
+use clap::{Args, Subcommand};
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+#[derive(Debug, Args)]
+pub struct WatchArgs {
+    /// Seconds between checks for changes
+    #[arg(long, default_value_t = 1, value_name = "SECONDS")]
+    interval: u64,
+    /// The report to keep on screen [default: today]
+    #[command(subcommand)]
+    view: Option<View>,
+}
+
+#[derive(Debug, Subcommand)]
+enum View {
+    /// The totals of every day, week or month
+    Report(ReportArgs),
+    /// Today's entries and the total so far
+    Today(Inputs),
+    /// A week's entries by day
+    Week(WeekArgs),
+    /// The time by tag, project or description
+    Summary(SummaryArgs),
+}
+
+impl View {
+    fn inputs(&self) -> &Inputs {
+        match self {
+            View::Report(args) => args.inputs(),
+            View::Today(inputs) => inputs,
+            View::Week(args) => args.inputs(),
+            View::Summary(args) => args.inputs(),
+        }
+    }
+
+    fn render(&self, global: &Global) -> Result<(), Box<dyn Error>> {
+        match self {
+            View::Report(args) => super::report::run(global, args),
+            View::Today(inputs) => super::today::run(global, inputs),
+            View::Week(args) => super::week::run(global, args),
+            View::Summary(args) => super::summary::run(global, args),
+        }
+    }
+}
+
+/// Clears the screen and prints the view again whenever one of its files
+/// changes, and every minute so running entries keep counting. Runs until
+/// interrupted
+pub fn run(global: &Global, args: &WatchArgs) -> Result<(), Box<dyn Error>> {
+    let today = View::Today(Inputs::default());
+    let view = args.view.as_ref().unwrap_or(&today);
+    let files = view.inputs().resolve(global)?;
+    if files.iter().any(|f| is_stdin(f)) {
+        return Err(UsageError("standard input can't be watched".to_string()).into());
+    }
+
+    let mut last = None;
+    loop {
+        let stamp = (modified(&files), now());
+        if last.as_ref() != Some(&stamp) {
+            print!("\x1b[2J\x1b[H");
+            // A half written file shouldn't end the watch, the next save
+            // probably fixes it
+            if let Err(e) = view.render(global) {
+                println!("error: {e}");
+            }
+            let updated = format!("\nUpdated {}, Ctrl-C to quit", stamp.1.format("%H:%M"));
+            println!("{}", paint(&updated, Style::Note, global.output.color));
+            io::stdout().flush()?;
+            last = Some(stamp);
+        }
+        thread::sleep(Duration::from_secs(args.interval.max(1)));
+    }
+}
+
+/// When each file was last changed, None for files that can't be read
+fn modified(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files
+        .iter()
+        .map(|f| fs::metadata(f).and_then(|m| m.modified()).ok())
+        .collect()
+}
@@ -0,0 +1,63 @@
+use super::table::{paint, Format, Style};
+use super::{entries_table, format_duration, print_day, Global, Inputs};
+use chrono::Duration;
+use clap::Args;
+use std::error::Error;
+use timetxt::Week;
+
+#[derive(Debug, Args)]
+pub struct WeekArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// Show last week instead of this one
+    #[arg(long, conflicts_with = "iso")]
+    last: bool,
+    /// Show an ISO week, Ex. 2024-W07. Weeks that don't start on Monday
+    /// are the ones sharing most of their days with it
+    #[arg(long, value_name = "YYYY-Www", value_parser = parse_iso_week)]
+    iso: Option<Week>,
+}
+
+impl WeekArgs {
+    pub fn inputs(&self) -> &Inputs {
+        &self.inputs
+    }
+}
+
+pub fn run(global: &Global, args: &WeekArgs) -> Result<(), Box<dyn Error>> {
+    let t = global.read(&args.inputs)?;
+    let now = global.now();
+
+    let week = match args.iso {
+        Some(week) => week.with_start(global.week_start),
+        None if args.last => Week::starting_on(now.date(), global.week_start).pred(),
+        None => Week::starting_on(now.date(), global.week_start),
+    };
+
+    let color = global.output.color;
+    if global.output.format != Format::Table {
+        return entries_table(global, &t, week.iter_days(), now).print(global.output);
+    }
+
+    let heading = week.to_string();
+    println!("{}\n", paint(&heading, Style::Bold, color));
+    let mut total = Duration::zero();
+    for date in week.iter_days() {
+        if t.entries_for(date).is_empty() && t.marker(date).is_none() {
+            continue;
+        }
+        total += print_day(global, &t, date, now);
+        println!();
+    }
+    let line = format!("{:<11}  {:>6}", "Week total", format_duration(total));
+    println!("{}", paint(&line, Style::Bold, color));
+    Ok(())
+}
+
+fn parse_iso_week(s: &str) -> Result<Week, String> {
+    let invalid = || format!("{s:?} isn't an ISO week like 2024-W07");
+    let (year, week) = s.split_once("-W").ok_or_else(invalid)?;
+    let year = year.parse().map_err(|_| invalid())?;
+    let week = week.parse().map_err(|_| invalid())?;
+    Week::from_iso(year, week).ok_or_else(invalid)
+}
@@ -0,0 +1,198 @@
+use crate::{classify_line, Line, TimeEntry};
+use chrono::NaiveDate;
+use std::collections::HashSet;
+use std::fmt;
+
+/// How serious a diagnostic is. Errors are lines `parse_time` rejects or
+/// drops, warnings are lines that parse but probably aren't what was meant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem found on a single line of a time.txt file
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    /// Line number starting at 1
+    pub line: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.severity, self.message)
+    }
+}
+
+/// Checks the contents of a time.txt file without building a `Time`
+/// and reports every problem found instead of stopping at the first one,
+/// sorted by line. An empty Vec means the file is clean
+#[must_use]
+pub fn validate(contents: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut date: Option<NaiveDate> = None;
+    let mut seen_dates = HashSet::new();
+    let mut marked_dates = HashSet::new();
+    let mut closed_entries: Vec<(usize, TimeEntry)> = Vec::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        let mut report = |severity, message: String| {
+            diagnostics.push(Diagnostic {
+                line: i + 1,
+                severity,
+                message,
+            });
+        };
+
+        match classify_line(line) {
+            Ok(Line::Ignored) => {}
+            Ok(Line::Skipped) => {
+                // Whitespace only lines are harmless, anything else is
+                // silently dropped by the parser
+                if !line.trim().is_empty() {
+                    report(
+                        Severity::Warning,
+                        format!("line is too short to be a date or entry: {line:?}"),
+                    );
+                }
+            }
+            Ok(Line::Date(d)) => {
+                if !seen_dates.insert(d) {
+                    report(
+                        Severity::Warning,
+                        format!("date {} appears more than once", d.format("%Y-%m-%d")),
+                    );
+                }
+                date = Some(d);
+            }
+            Ok(Line::Marker(_)) => match date {
+                None => report(
+                    Severity::Error,
+                    "day marker found before any date header".to_string(),
+                ),
+                Some(d) => {
+                    if !marked_dates.insert(d) {
+                        report(
+                            Severity::Warning,
+                            "day already has a marker, only the last one is used".to_string(),
+                        );
+                    }
+                }
+            },
+            Ok(Line::Entry(e)) => {
+                if date.is_none() {
+                    report(
+                        Severity::Error,
+                        "entry found before any date header".to_string(),
+                    );
+                }
+                if e.end == Some(e.start) {
+                    report(
+                        Severity::Warning,
+                        "entry starts and ends at the same time".to_string(),
+                    );
+                }
+                if e.description.is_empty() {
+                    report(Severity::Warning, "entry has no description".to_string());
+                }
+                if let Some(d) = date {
+                    let e = e.into_entry(d);
+                    if !e.cancelled && !e.is_open() {
+                        closed_entries.push((i + 1, e));
+                    }
+                }
+            }
+            Err(err) => report(Severity::Error, err.to_string()),
+        }
+    }
+
+    for (i, (line, e)) in closed_entries.iter().enumerate() {
+        let overlapped = closed_entries[..i].iter().find(|(_, other)| {
+            other.date == e.date
+                && other.start_datetime() < e.end_datetime().unwrap_or(e.start_datetime())
+                && e.start_datetime() < other.end_datetime().unwrap_or(other.start_datetime())
+        });
+        if let Some((other_line, _)) = overlapped {
+            diagnostics.push(Diagnostic {
+                line: *line,
+                severity: Severity::Warning,
+                message: format!("entry overlaps the entry on line {other_line}"),
+            });
+        }
+    }
+    diagnostics.sort_by_key(|d| d.line);
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let input = "// Comment\n\
+        9:00 10:00 Orphan entry\n\
+        1822-01-15\n\
+        3:00 4:00 Sketched ideas\n\
+        3:30 4:30 Overlapping ideas\n\
+        25:00 26:00 Impossible hours\n\
+        junk\n\
+        5:00 5:00 \n\
+        1822-01-15\n\
+        VACATION\n\
+        SICK\n";
+
+        let found: Vec<(usize, Severity)> = validate(input)
+            .into_iter()
+            .map(|d| (d.line, d.severity))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (2, Severity::Error),
+                (5, Severity::Warning),
+                (6, Severity::Error),
+                (7, Severity::Warning),
+                (8, Severity::Warning),
+                (8, Severity::Warning),
+                (9, Severity::Warning),
+                (11, Severity::Warning),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_clean() {
+        let input = "1822-01-15\n\
+        3:00 4:00 Sketched ideas for a new machine\n";
+
+        assert!(validate(input).is_empty());
+    }
+
+    #[test]
+    fn test_validate_non_ascii() {
+        let input = "1822-01-15\n\
+        ééééé 10:00 Not a time\n\
+        9:00 10:00 Café ☕\n\
+        ☕\n";
+
+        let found: Vec<(usize, Severity)> = validate(input)
+            .into_iter()
+            .map(|d| (d.line, d.severity))
+            .collect();
+        assert_eq!(found, vec![(2, Severity::Error), (4, Severity::Warning)]);
+    }
+}
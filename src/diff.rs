@@ -0,0 +1,167 @@
+use crate::{DayMarker, Time, TimeEntry};
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// What changed between two versions of a file, see `Time::diff`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimeDiff<'a> {
+    /// Only dates with changes are included
+    pub days: BTreeMap<NaiveDate, DayDiff<'a>>,
+}
+
+/// The changes to a single date
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DayDiff<'a> {
+    /// Entries only in the new version
+    pub added: Vec<&'a TimeEntry>,
+    /// Entries only in the old version
+    pub removed: Vec<&'a TimeEntry>,
+    /// Entries that start at the same time in both versions but
+    /// differ otherwise, as (old, new)
+    pub modified: Vec<(&'a TimeEntry, &'a TimeEntry)>,
+    /// The day's marker as (old, new) when it was added, removed or
+    /// changed
+    pub marker: Option<(Option<DayMarker>, Option<DayMarker>)>,
+}
+
+impl TimeDiff<'_> {
+    /// True when both versions have the same entries and markers
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.days.is_empty()
+    }
+}
+
+impl DayDiff<'_> {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && self.marker.is_none()
+    }
+}
+
+impl Time {
+    /// Compares `self` as the old version against `other` as the new one.
+    /// Entry order within a day doesn't matter
+    #[must_use]
+    pub fn diff<'a>(&'a self, other: &'a Time) -> TimeDiff<'a> {
+        let mut diff = TimeDiff::default();
+        let dates: BTreeSet<NaiveDate> = self.days().chain(other.days()).collect();
+
+        for date in dates {
+            let mut old: Vec<&TimeEntry> = self.entries_for(date).iter().collect();
+            let mut new: Vec<&TimeEntry> = Vec::new();
+            for e in other.entries_for(date) {
+                // Identical entries on both sides are unchanged
+                match old.iter().position(|o| *o == e) {
+                    Some(i) => {
+                        old.remove(i);
+                    }
+                    None => new.push(e),
+                }
+            }
+
+            let mut day = DayDiff::default();
+            for e in new {
+                match old.iter().position(|o| o.start == e.start) {
+                    Some(i) => day.modified.push((old.remove(i), e)),
+                    None => day.added.push(e),
+                }
+            }
+            day.removed = old;
+            if self.marker(date) != other.marker(date) {
+                day.marker = Some((self.marker(date), other.marker(date)));
+            }
+
+            if !day.is_empty() {
+                diff.days.insert(date, day);
+            }
+        }
+
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_time, DayMarker, MarkerKind};
+    use chrono::NaiveDate;
+    use std::error::Error;
+
+    #[test]
+    fn test_diff() -> Result<(), Box<dyn Error>> {
+        let old = parse_time(
+            "1822-01-15\n\
+            3:00 4:00 Sketched ideas\n\
+            4:00 11:00 Created the first computer\n\
+            1822-01-16\n\
+            15:30 17:30 Decided on the name\n",
+        )?;
+        let new = parse_time(
+            "1822-01-15\n\
+            4:00 12:00 Created the first computer\n\
+            3:00 4:00 Sketched ideas\n\
+            1822-01-17\n\
+            9:00 10:00 Told everyone\n",
+        )?;
+        let d = |day| NaiveDate::from_ymd_opt(1822, 1, day).unwrap();
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.days.len(), 3);
+
+        let day = &diff.days[&d(15)];
+        assert!(day.added.is_empty() && day.removed.is_empty());
+        assert_eq!(day.modified.len(), 1);
+        assert_eq!(
+            format!("{}", day.modified[0].1),
+            "04:00 12:00 Created the first computer"
+        );
+
+        assert_eq!(diff.days[&d(16)].removed.len(), 1);
+        assert_eq!(diff.days[&d(17)].added.len(), 1);
+        assert!(old.diff(&old).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_markers() -> Result<(), Box<dyn Error>> {
+        let old = parse_time(
+            "1822-01-15\n\
+            SICK\n\
+            1822-01-16\n\
+            VACATION\n\
+            1822-01-17\n\
+            9:00 10:00 Told everyone\n",
+        )?;
+        let new = parse_time(
+            "1822-01-15\n\
+            SICK half\n\
+            1822-01-17\n\
+            HOLIDAY\n\
+            9:00 10:00 Told everyone\n",
+        )?;
+        let d = |day| NaiveDate::from_ymd_opt(1822, 1, day).unwrap();
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.days.len(), 3);
+        let marker = |kind, half_day| Some(DayMarker { kind, half_day });
+        assert_eq!(
+            diff.days[&d(15)].marker,
+            Some((
+                marker(MarkerKind::Sick, false),
+                marker(MarkerKind::Sick, true)
+            ))
+        );
+        assert_eq!(
+            diff.days[&d(16)].marker,
+            Some((marker(MarkerKind::Vacation, false), None))
+        );
+        assert_eq!(
+            diff.days[&d(17)].marker,
+            Some((None, marker(MarkerKind::Holiday, false)))
+        );
+        assert!(diff.days[&d(17)].added.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use crate::ParseError;
+use chrono::Duration;
+
+/// Parses a length of time written by hand, Ex. `8h`, `7h30m`, `90m`,
+/// `1.5h`, `7:30` or `8`. A bare number is a number of hours
+///
+/// # Errors
+/// Errors with `ParseError::InvalidDuration` for anything else, including
+/// negative durations
+pub fn parse_duration(s: &str) -> Result<Duration, ParseError> {
+    let invalid = || ParseError::InvalidDuration(s.to_string());
+    let text = s.trim().to_lowercase();
+    if text.is_empty() {
+        return Err(invalid());
+    }
+
+    if let Some((h, m)) = text.split_once(':') {
+        let h: i64 = h.parse().map_err(|_| invalid())?;
+        let m: i64 = m.parse().map_err(|_| invalid())?;
+        if h < 0 || !(0..60).contains(&m) {
+            return Err(invalid());
+        }
+        return h
+            .checked_mul(60)
+            .and_then(|minutes| minutes.checked_add(m))
+            .and_then(Duration::try_minutes)
+            .ok_or_else(invalid);
+    }
+
+    if let Ok(hours) = text.parse::<f64>() {
+        return minutes(hours * 60.0).ok_or_else(invalid);
+    }
+
+    let mut total = 0.0;
+    let mut rest = text.as_str();
+    while !rest.is_empty() {
+        let unit = rest.find(['h', 'm']).ok_or_else(invalid)?;
+        let value: f64 = rest[..unit].parse().map_err(|_| invalid())?;
+        total += if rest[unit..].starts_with('h') {
+            value * 60.0
+        } else {
+            value
+        };
+        rest = &rest[unit + 1..];
+    }
+    minutes(total).ok_or_else(invalid)
+}
+
+/// Rounds to whole minutes, None for negative or absurd values
+fn minutes(minutes: f64) -> Option<Duration> {
+    if minutes.is_finite() && (0.0..1e12).contains(&minutes) {
+        // Checked above so the cast can't truncate
+        #[allow(clippy::cast_possible_truncation)]
+        Some(Duration::minutes(minutes.round() as i64))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() -> Result<(), ParseError> {
+        assert_eq!(parse_duration("8h")?, Duration::hours(8));
+        assert_eq!(parse_duration("7h30m")?, Duration::minutes(450));
+        assert_eq!(parse_duration("90m")?, Duration::minutes(90));
+        assert_eq!(parse_duration("1.5h")?, Duration::minutes(90));
+        assert_eq!(parse_duration("7:05")?, Duration::minutes(425));
+        assert_eq!(parse_duration(" 8 ")?, Duration::hours(8));
+
+        for bad in [
+            "",
+            "h",
+            "-1h",
+            "7:75",
+            "5x",
+            "2h5",
+            "1:2:3",
+            "999999999999999:00",
+            "9223372036854775807:00",
+        ] {
+            assert_eq!(
+                parse_duration(bad),
+                Err(ParseError::InvalidDuration(bad.to_string()))
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,310 @@
+use crate::{ParseError, Time, TimeEntry};
+use chrono::{Duration, NaiveDate, NaiveTime};
+use std::ops::{Bound, RangeBounds};
+
+impl Time {
+    /// Adds an entry under its date, after any entries already there
+    ///
+    /// # Errors
+    /// Errors if the entry couldn't be written back to a time.txt file,
+    /// see `update_entry`
+    pub fn add_entry(&mut self, entry: TimeEntry) -> Result<(), ParseError> {
+        check_entry(&entry)?;
+        self.entries.entry(entry.date).or_default().push(entry);
+        Ok(())
+    }
+
+    /// Removes the entry at `index` of a day, dropping the day once it has
+    /// no entries left. Returns None if there's no such entry
+    pub fn remove_entry(&mut self, date: NaiveDate, index: usize) -> Option<TimeEntry> {
+        let entries = self.entries.get_mut(&date)?;
+        if index >= entries.len() {
+            return None;
+        }
+
+        let removed = entries.remove(index);
+        if entries.is_empty() {
+            self.entries.remove(&date);
+        }
+        Some(removed)
+    }
+
+    /// Replaces the entry at `index` of a day and returns the old one.
+    /// If the new entry has a different date it's moved to the end of
+    /// that day
+    ///
+    /// # Errors
+    /// Errors if there's no entry at `index` or the new entry is invalid.
+    /// Entries must not start and end at the same time and their
+    /// description has to fit on one line
+    pub fn update_entry(
+        &mut self,
+        date: NaiveDate,
+        index: usize,
+        entry: TimeEntry,
+    ) -> Result<TimeEntry, ParseError> {
+        check_entry(&entry)?;
+
+        let entries = self.entries.get_mut(&date).filter(|e| index < e.len());
+        let Some(entries) = entries else {
+            return Err(ParseError::InvalidEntry(format!(
+                "no entry {index} on {date}"
+            )));
+        };
+
+        if entry.date == date {
+            return Ok(std::mem::replace(&mut entries[index], entry));
+        }
+
+        let old = entries.remove(index);
+        if entries.is_empty() {
+            self.entries.remove(&date);
+        }
+        self.entries.entry(entry.date).or_default().push(entry);
+        Ok(old)
+    }
+
+    /// Keeps only the entries for which `keep` returns true, dropping days
+    /// left without entries. Markers are kept
+    pub fn retain<F: FnMut(NaiveDate, &TimeEntry) -> bool>(&mut self, mut keep: F) {
+        for (date, entries) in &mut self.entries {
+            entries.retain(|e| keep(*date, e));
+        }
+        self.entries.retain(|_, entries| !entries.is_empty());
+    }
+
+    /// Removes entries that are exactly the same as an earlier entry of
+    /// their day, Ex. after merging files with a day in common. Returns the
+    /// removed copies in date order
+    pub fn remove_duplicates(&mut self) -> Vec<TimeEntry> {
+        let mut removed = Vec::new();
+        for entries in self.entries.values_mut() {
+            let mut seen: Vec<TimeEntry> = Vec::with_capacity(entries.len());
+            for e in entries.drain(..) {
+                if seen.contains(&e) {
+                    removed.push(e);
+                } else {
+                    seen.push(e);
+                }
+            }
+            *entries = seen;
+        }
+
+        removed
+    }
+
+    /// Removes every day in `range` and returns them as a new Time,
+    /// Ex. `t.drain_range(..cutoff)` to prune old history
+    #[must_use = "the drained days are returned, use `let _ =` to drop them"]
+    pub fn drain_range<R: RangeBounds<NaiveDate>>(&mut self, range: R) -> Time {
+        let bounds: (Bound<NaiveDate>, Bound<NaiveDate>) =
+            (range.start_bound().cloned(), range.end_bound().cloned());
+
+        let mut drained = Time::new();
+        let dates: Vec<NaiveDate> = self.entries.range(bounds).map(|(d, _)| *d).collect();
+        for date in dates {
+            if let Some(entries) = self.entries.remove(&date) {
+                drained.entries.insert(date, entries);
+            }
+        }
+        let dates: Vec<NaiveDate> = self.markers.range(bounds).map(|(d, _)| *d).collect();
+        for date in dates {
+            if let Some(m) = self.markers.remove(&date) {
+                drained.markers.insert(date, m);
+            }
+        }
+
+        drained
+    }
+
+    /// Joins back to back entries of a day that have the same description
+    /// and at most `max_gap` between them, Ex. noisy auto tracked data.
+    /// The day's entries end up sorted by start time. Returns how many
+    /// entries were merged away
+    pub fn merge_adjacent(&mut self, date: NaiveDate, max_gap: Duration) -> usize {
+        let Some(entries) = self.entries.get_mut(&date) else {
+            return 0;
+        };
+        entries.sort_by_key(|e| e.start);
+
+        let before = entries.len();
+        let mut merged: Vec<TimeEntry> = Vec::with_capacity(before);
+        for e in entries.drain(..) {
+            if let Some(prev) = merged.last_mut() {
+                if let (Some(prev_end), Some(end)) = (prev.end, e.end) {
+                    let mergeable = !prev.cancelled
+                        && !e.cancelled
+                        && prev.description == e.description
+                        // Entries running past midnight are left alone
+                        && prev_end > prev.start
+                        && end > e.start
+                        && e.start.signed_duration_since(prev_end) <= max_gap;
+
+                    if mergeable {
+                        prev.end = Some(prev_end.max(end));
+                        continue;
+                    }
+                }
+            }
+            merged.push(e);
+        }
+
+        let removed = before - merged.len();
+        *entries = merged;
+        removed
+    }
+}
+
+impl TimeEntry {
+    /// Splits the entry in two at `time`, the first part ending and the
+    /// second starting there. Returns None unless `time` is strictly
+    /// between the start and end, open entries can be split anywhere after
+    /// their start and the second part stays open
+    #[must_use]
+    pub fn split_at(&self, time: NaiveTime) -> Option<(TimeEntry, TimeEntry)> {
+        let inside = match self.end {
+            // Entries running past midnight cover the end of one day and
+            // the start of the next
+            Some(end) if end < self.start => time > self.start || time < end,
+            Some(end) => time > self.start && time < end,
+            None => time > self.start,
+        };
+        if !inside {
+            return None;
+        }
+
+        let first = TimeEntry {
+            end: Some(time),
+            ..self.clone()
+        };
+        let second = TimeEntry {
+            start: time,
+            ..self.clone()
+        };
+        Some((first, second))
+    }
+}
+
+pub(crate) fn check_entry(entry: &TimeEntry) -> Result<(), ParseError> {
+    if entry.end == Some(entry.start) {
+        return Err(ParseError::InvalidEntry(
+            "entry starts and ends at the same time".to_string(),
+        ));
+    }
+
+    if entry.description.contains(['\n', '\r']) {
+        return Err(ParseError::InvalidEntry(
+            "entry description must be a single line".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_time, TimeEntry};
+    use chrono::{Duration, NaiveDate, NaiveTime};
+    use std::error::Error;
+
+    #[test]
+    fn test_edit_entries() -> Result<(), Box<dyn Error>> {
+        let mut t = parse_time("1822-01-15\n3:00 4:00 Sketched ideas\n")?;
+        let d = |day| NaiveDate::from_ymd_opt(1822, 1, day).unwrap();
+
+        t.add_entry("1822-01-15 9:00 11:00 Created the first computer".parse()?)?;
+        assert!(t
+            .add_entry("1822-01-15 9:00 9:00 Nothing at all".parse()?)
+            .is_err());
+
+        let mut multiline: TimeEntry = "1822-01-15 12:00 13:00 Lunch".parse()?;
+        multiline.description.push_str("\n1822-01-16");
+        assert!(t.add_entry(multiline).is_err());
+
+        let old = t.update_entry(d(15), 0, "1822-01-16 3:00 5:00 Sketched more".parse()?)?;
+        assert_eq!(old.description, "Sketched ideas");
+        assert!(t.update_entry(d(15), 5, old).is_err());
+
+        assert_eq!(
+            format!("{t}"),
+            "1822-01-15\n09:00 11:00 Created the first computer\n\
+            1822-01-16\n03:00 05:00 Sketched more\n"
+        );
+
+        assert!(t.remove_entry(d(16), 1).is_none());
+        assert!(t.remove_entry(d(16), 0).is_some());
+        assert!(!t.entries.contains_key(&d(16)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_and_merge() -> Result<(), Box<dyn Error>> {
+        let e: TimeEntry = "1822-01-15 9:00 12:00 Built the engine".parse()?;
+        let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+        let ten = NaiveTime::from_hms_opt(10, 0, 0).unwrap();
+
+        let (first, second) = e.split_at(ten).unwrap();
+        assert_eq!(format!("{first}"), "09:00 10:00 Built the engine");
+        assert_eq!(format!("{second}"), "10:00 12:00 Built the engine");
+        assert!(e.split_at(noon).is_none());
+
+        let input = "1822-01-15\n\
+        9:00 10:00 Built the engine\n\
+        10:02 11:00 Built the engine\n\
+        11:00 11:30 Lunch\n\
+        11:30 12:00 Built the engine\n\
+        12:00 13:00 Built the engine\n";
+        let mut t = parse_time(input)?;
+        let d = NaiveDate::from_ymd_opt(1822, 1, 15).unwrap();
+
+        assert_eq!(t.merge_adjacent(d, Duration::minutes(5)), 2);
+        assert_eq!(
+            format!("{t}"),
+            "1822-01-15\n\
+            09:00 11:00 Built the engine\n\
+            11:00 11:30 Lunch\n\
+            11:30 13:00 Built the engine\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_retain_and_drain() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-14\n\
+        VACATION\n\
+        1822-01-15\n\
+        3:00 4:00 Sketched ideas\n\
+        x 4:00 5:00 Cancelled\n\
+        1822-01-16\n\
+        x 9:00 10:00 Cancelled too\n\
+        1822-01-17\n\
+        15:30 17:30 Decided on the name\n";
+        let d = |day| NaiveDate::from_ymd_opt(1822, 1, day).unwrap();
+
+        let mut t = parse_time(input)?;
+        t.retain(|_, e| !e.cancelled);
+        assert_eq!(t.len(), 2);
+        assert_eq!(t.days().collect::<Vec<_>>(), vec![d(14), d(15), d(17)]);
+
+        let old = t.drain_range(..d(16));
+        assert_eq!(old.days().collect::<Vec<_>>(), vec![d(14), d(15)]);
+        assert_eq!(t.days().collect::<Vec<_>>(), vec![d(17)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_duplicates() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-15\n\
+        3:00 4:00 Sketched ideas\n\
+        1822-01-16\n\
+        15:30 17:30 Decided on the name\n";
+
+        let t = parse_time(input)?;
+        let mut merged = t.clone().merge(t.clone());
+        assert_eq!(merged.len(), 4);
+        assert_eq!(merged.remove_duplicates().len(), 2);
+        assert_eq!(merged, t);
+        assert!(merged.remove_duplicates().is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use std::error::Error;
+use std::fmt;
+
+/// Everything that can go wrong reading or building time entries.
+/// Errors from `parse_time` carry the 1 based line they were found on,
+/// errors from single lines or the builder don't have one
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A date that isn't in the `YYYY-MM-DD` format
+    BadDate {
+        line: Option<usize>,
+        source: chrono::ParseError,
+    },
+    /// A start or end time that isn't in the `H:MM` format
+    BadTime {
+        line: Option<usize>,
+        source: chrono::ParseError,
+    },
+    /// A line that isn't a date, marker or comment but doesn't start
+    /// with a time
+    MissingStartTime { line: Option<usize> },
+    /// An entry with a start time but no end time or `--:--`
+    MissingEndTime { line: Option<usize> },
+    /// An entry or marker before the first date of the file
+    MissingDateHeader { line: Option<usize> },
+    /// An entry that can't be written back to a time.txt file,
+    /// Ex. one that starts and ends at the same time
+    InvalidEntry(String),
+    /// A query passed to `Query::parse` that doesn't follow its syntax
+    InvalidQuery(String),
+    /// A period passed to `DateRange::parse_period` that isn't one of the
+    /// forms it knows
+    InvalidPeriod(String),
+    /// A duration passed to `parse_duration` that isn't in one of the
+    /// forms it knows
+    InvalidDuration(String),
+}
+
+impl ParseError {
+    /// The line the error was found on, if it came from a file
+    #[must_use]
+    pub fn line(&self) -> Option<usize> {
+        match *self {
+            ParseError::BadDate { line, .. }
+            | ParseError::BadTime { line, .. }
+            | ParseError::MissingStartTime { line }
+            | ParseError::MissingEndTime { line }
+            | ParseError::MissingDateHeader { line } => line,
+            ParseError::InvalidEntry(_)
+            | ParseError::InvalidQuery(_)
+            | ParseError::InvalidPeriod(_)
+            | ParseError::InvalidDuration(_) => None,
+        }
+    }
+
+    pub(crate) fn at_line(mut self, n: usize) -> ParseError {
+        match self {
+            ParseError::BadDate { ref mut line, .. }
+            | ParseError::BadTime { ref mut line, .. }
+            | ParseError::MissingStartTime { ref mut line }
+            | ParseError::MissingEndTime { ref mut line }
+            | ParseError::MissingDateHeader { ref mut line } => *line = Some(n),
+            ParseError::InvalidEntry(_)
+            | ParseError::InvalidQuery(_)
+            | ParseError::InvalidPeriod(_)
+            | ParseError::InvalidDuration(_) => {}
+        }
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line() {
+            write!(f, "line {line}: ")?;
+        }
+
+        match *self {
+            ParseError::BadDate { ref source, .. } => write!(f, "invalid date: {source}"),
+            ParseError::BadTime { ref source, .. } => write!(f, "invalid time: {source}"),
+            ParseError::MissingStartTime { .. } => write!(f, "start time not found"),
+            ParseError::MissingEndTime { .. } => write!(f, "end time not found"),
+            ParseError::MissingDateHeader { .. } => write!(f, "no date header before this line"),
+            ParseError::InvalidEntry(ref s) => write!(f, "{s}"),
+            ParseError::InvalidQuery(ref s) => write!(f, "invalid query: {s}"),
+            ParseError::InvalidPeriod(ref s) => write!(f, "invalid period: {s}"),
+            ParseError::InvalidDuration(ref s) => write!(f, "invalid duration: {s}"),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ParseError::BadDate { ref source, .. } | ParseError::BadTime { ref source, .. } => {
+                Some(source)
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{Time, TimeEntry};
+
+/// Something an entry's description can be matched against. Plain strings
+/// match as a case insensitive substring and with the `regex` feature a
+/// `regex::Regex` can be used as well
+pub trait DescriptionPattern {
+    fn is_match(&self, description: &str) -> bool;
+}
+
+impl DescriptionPattern for str {
+    fn is_match(&self, description: &str) -> bool {
+        description.to_lowercase().contains(&self.to_lowercase())
+    }
+}
+
+impl DescriptionPattern for String {
+    fn is_match(&self, description: &str) -> bool {
+        self.as_str().is_match(description)
+    }
+}
+
+#[cfg(feature = "regex")]
+impl DescriptionPattern for regex::Regex {
+    fn is_match(&self, description: &str) -> bool {
+        self.is_match(description)
+    }
+}
+
+impl Time {
+    /// A copy holding only the entries for which `keep` returns true.
+    /// Days left without entries are dropped along with their markers
+    #[must_use]
+    pub fn filter<F: Fn(&TimeEntry) -> bool>(&self, keep: F) -> Time {
+        let mut t = Time::new();
+        for (date, entries) in &self.entries {
+            let matching: Vec<TimeEntry> = entries.iter().filter(|e| keep(e)).cloned().collect();
+
+            if !matching.is_empty() {
+                t.entries.insert(*date, matching);
+                if let Some(m) = self.marker(*date) {
+                    t.markers.insert(*date, m);
+                }
+            }
+        }
+
+        t
+    }
+
+    /// A copy holding only the entries whose description matches `pattern`
+    #[must_use]
+    pub fn filter_description<P: DescriptionPattern + ?Sized>(&self, pattern: &P) -> Time {
+        self.filter(|e| pattern.is_match(&e.description))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_time;
+    use chrono::Duration;
+    use std::error::Error;
+
+    const INPUT: &str = "1822-01-14\n\
+        3:00 4:00 Code review with Ada\n\
+        4:00 6:00 Sketched ideas\n\
+        1822-01-15\n\
+        9:00 11:00 Reviewed the first computer\n\
+        1822-01-16\n\
+        15:30 17:30 Decided on the name\n";
+
+    #[test]
+    fn test_filter_description() -> Result<(), Box<dyn Error>> {
+        let t = parse_time(INPUT)?.filter_description("review");
+        assert_eq!(t.total(), Duration::hours(3));
+        assert_eq!(t.entries.len(), 2);
+        Ok(())
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_filter_description_regex() -> Result<(), Box<dyn Error>> {
+        let re = regex::Regex::new(r"^(Sketched|Decided)")?;
+        let t = parse_time(INPUT)?.filter_description(&re);
+        assert_eq!(t.total(), Duration::hours(4));
+        Ok(())
+    }
+}
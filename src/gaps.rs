@@ -0,0 +1,98 @@
+use crate::{DateRange, Time};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+/// Untracked time between two entries of the same day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Gap {
+    pub date: NaiveDate,
+    /// End of the entry before the gap
+    pub start: NaiveDateTime,
+    /// Start of the entry after the gap
+    pub end: NaiveDateTime,
+}
+
+impl Gap {
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.end.signed_duration_since(self.start)
+    }
+}
+
+impl Time {
+    /// Stretches of at least `min` between consecutive entries within a
+    /// day, useful for spotting forgotten entries. Time before the first
+    /// and after the last entry of a day isn't a gap. Open and cancelled
+    /// entries are ignored
+    #[must_use]
+    pub fn gaps(&self, min: Duration) -> Vec<Gap> {
+        let mut gaps = Vec::new();
+        for date in self.days() {
+            let mut spans: Vec<_> = self
+                .entries_for(date)
+                .iter()
+                .filter(|e| !e.cancelled)
+                .filter_map(|e| Some((e.start_datetime(), e.end_datetime()?)))
+                .collect();
+            spans.sort();
+
+            // Overlapping entries can end after the one that follows
+            // them, so measure from the latest end seen so far
+            let mut tracked_until: Option<NaiveDateTime> = None;
+            for (start, end) in spans {
+                if let Some(until) = tracked_until {
+                    let gap = start.signed_duration_since(until);
+                    if gap > Duration::zero() && gap >= min {
+                        gaps.push(Gap {
+                            date,
+                            start: until,
+                            end: start,
+                        });
+                    }
+                }
+                tracked_until = Some(tracked_until.map_or(end, |until| until.max(end)));
+            }
+        }
+
+        gaps
+    }
+
+    /// Days in `range` with neither entries nor a vacation, sick or holiday marker
+    #[must_use]
+    pub fn untracked_days<R: Into<DateRange>>(&self, range: R) -> Vec<NaiveDate> {
+        range
+            .into()
+            .iter_days()
+            .filter(|d| self.entries_for(*d).is_empty() && self.marker(*d).is_none())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_time;
+    use chrono::{Duration, NaiveDate};
+    use std::error::Error;
+
+    #[test]
+    fn test_gaps() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-15\n\
+        3:00 5:00 Sketched ideas\n\
+        4:00 4:30 Overlapping tea\n\
+        5:10 6:00 Created the first computer\n\
+        8:00 9:00 Decided on the name\n\
+        1822-01-17\n\
+        SICK\n";
+
+        let t = parse_time(input)?;
+        let d = |day| NaiveDate::from_ymd_opt(1822, 1, day).unwrap();
+
+        let gaps = t.gaps(Duration::minutes(5));
+        assert_eq!(gaps.len(), 2);
+        assert_eq!(gaps[0].duration(), Duration::minutes(10));
+        assert_eq!(gaps[1].duration(), Duration::hours(2));
+        assert_eq!(t.gaps(Duration::minutes(15)).len(), 1);
+
+        assert_eq!(t.untracked_days(d(14)..=d(18)), vec![d(14), d(16), d(18)]);
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{Time, TimeEntry};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Bumped when a field changes meaning or goes away, new fields don't
+const VERSION: u32 = 1;
+
+impl Time {
+    /// Every day as JSON for other tools, durations in minutes:
+    ///
+    /// ```json
+    /// {
+    ///   "version": 1,
+    ///   "total_minutes": 90,
+    ///   "days": [
+    ///     {
+    ///       "date": "2024-03-04",
+    ///       "marker": "SICK half",
+    ///       "total_minutes": 90,
+    ///       "entries": [
+    ///         {
+    ///           "start": "09:00",
+    ///           "end": "10:30",
+    ///           "duration_minutes": 90,
+    ///           "description": "Fixed login +web #bug",
+    ///           "tags": ["bug"],
+    ///           "projects": ["web"],
+    ///           "contexts": [],
+    ///           "meta": {}
+    ///         }
+    ///       ]
+    ///     }
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// `marker` is null for regular days and `end` for running entries,
+    /// whose duration is 0. Cancelled entries are left out, like they are
+    /// from the totals. Requires the `serde` feature
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let days = self
+            .days()
+            .map(|date| {
+                let mut entries: Vec<&TimeEntry> = self
+                    .entries_for(date)
+                    .iter()
+                    .filter(|e| !e.cancelled)
+                    .collect();
+                entries.sort_by_key(|e| e.start);
+                Day {
+                    date: date.format("%Y-%m-%d").to_string(),
+                    marker: self.marker(date).map(|m| m.to_string()),
+                    total_minutes: self.total_for(date).num_minutes(),
+                    entries: entries.into_iter().map(Entry::from).collect(),
+                }
+            })
+            .collect();
+        let export = Export {
+            version: VERSION,
+            total_minutes: self.total().num_minutes(),
+            days,
+        };
+        // Strings, numbers and maps with string keys always serialize
+        serde_json::to_string_pretty(&export).unwrap_or_default()
+    }
+}
+
+#[derive(Serialize)]
+struct Export<'a> {
+    version: u32,
+    total_minutes: i64,
+    days: Vec<Day<'a>>,
+}
+
+#[derive(Serialize)]
+struct Day<'a> {
+    date: String,
+    marker: Option<String>,
+    total_minutes: i64,
+    entries: Vec<Entry<'a>>,
+}
+
+#[derive(Serialize)]
+struct Entry<'a> {
+    start: String,
+    end: Option<String>,
+    duration_minutes: i64,
+    description: &'a str,
+    tags: Vec<&'a str>,
+    projects: Vec<&'a str>,
+    contexts: Vec<&'a str>,
+    meta: BTreeMap<&'a str, &'a str>,
+}
+
+impl<'a> From<&'a TimeEntry> for Entry<'a> {
+    fn from(e: &'a TimeEntry) -> Entry<'a> {
+        Entry {
+            start: e.start.format("%H:%M").to_string(),
+            end: e.end.map(|t| t.format("%H:%M").to_string()),
+            duration_minutes: e.duration().num_minutes(),
+            description: &e.description,
+            tags: e.tags(),
+            projects: e.projects(),
+            contexts: e.contexts(),
+            meta: e.meta(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_time;
+    use serde_json::{json, Value};
+    use std::error::Error;
+
+    #[test]
+    fn test_to_json() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-15\n\
+        SICK half\n\
+        4:00 5:30 Sketched ideas +engine #design\n\
+        x 6:00 7:00 Cancelled\n\
+        1822-01-16\n\
+        9:00 --:-- Building client:babbage\n";
+
+        let json: Value = serde_json::from_str(&parse_time(input)?.to_json())?;
+        assert_eq!(json["version"], 1);
+        assert_eq!(json["total_minutes"], 90);
+        let days = json["days"].as_array().unwrap();
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0]["date"], "1822-01-15");
+        assert_eq!(days[0]["marker"], "SICK half");
+        assert_eq!(days[0]["total_minutes"], 90);
+        assert_eq!(
+            days[0]["entries"][0],
+            json!({
+                "start": "04:00",
+                "end": "05:30",
+                "duration_minutes": 90,
+                "description": "Sketched ideas +engine #design",
+                "tags": ["design"],
+                "projects": ["engine"],
+                "contexts": [],
+                "meta": {}
+            })
+        );
+        assert_eq!(days[0]["entries"].as_array().unwrap().len(), 1);
+        assert_eq!(days[1]["marker"], Value::Null);
+        assert_eq!(days[1]["entries"][0]["end"], Value::Null);
+        assert_eq!(days[1]["entries"][0]["meta"]["client"], "babbage");
+        Ok(())
+    }
+}
@@ -2,77 +2,572 @@
 #![deny(clippy::pedantic)]
 #![warn(rust_2018_idioms)]
 
-use chrono::{Duration, NaiveDate, NaiveTime};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
 use log::debug;
-use std::collections::HashMap;
-use std::error::Error;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
+use std::str::FromStr;
 
-#[allow(dead_code)]
-#[derive(Debug)]
-enum TimeError {
-    DateParse(chrono::ParseError),
-    TimeParse(chrono::ParseError),
-    ParseError(chrono::ParseError),
-    TimeNotFound(String),
+mod aggregate;
+mod builder;
+mod diagnostics;
+mod diff;
+mod duration;
+mod edit;
+mod error;
+mod filter;
+mod gaps;
+#[cfg(feature = "serde")]
+mod json;
+mod normalize;
+mod overlap;
+mod period;
+mod query;
+mod rounding;
+mod save;
+mod serialize;
+mod stats;
+mod text;
+
+pub use builder::{TimeEntryBuilder, TimeInput};
+pub use diagnostics::{validate, Diagnostic, Severity};
+pub use diff::{DayDiff, TimeDiff};
+pub use duration::parse_duration;
+pub use error::ParseError;
+pub use filter::DescriptionPattern;
+pub use gaps::Gap;
+pub use normalize::NormalizeReport;
+pub use overlap::Overlap;
+pub use period::{DateRange, Month, Week};
+pub use query::Query;
+pub use rounding::{RoundPer, Rounding, RoundingMode};
+pub use save::{write_atomic, SaveOptions};
+pub use stats::Stats;
+pub use text::{
+    amend_latest_entry, amend_open_entry, close_open_entry, format_time_txt, insert_date,
+    insert_entry, remove_duplicate_entries, remove_open_entry, sort_time_txt, take_days,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Time {
+    entries: BTreeMap<NaiveDate, Vec<TimeEntry>>,
+    /// Days marked as vacation, sick or holiday, at most one marker per day
+    markers: BTreeMap<NaiveDate, DayMarker>,
 }
 
-impl fmt::Display for TimeError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match *self {
-            TimeError::DateParse(ref err)
-            | TimeError::TimeParse(ref err)
-            | TimeError::ParseError(ref err) => err.fmt(f),
-            TimeError::TimeNotFound(ref s) => write!(f, "{}", s),
+impl Time {
+    /// An empty Time without any days
+    #[must_use]
+    pub fn new() -> Time {
+        Time {
+            entries: BTreeMap::new(),
+            markers: BTreeMap::new(),
         }
     }
-}
 
-impl Error for TimeError {}
+    /// Every date with entries or a marker, in order
+    pub fn days(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        let dates: BTreeSet<&NaiveDate> = self.entries.keys().chain(self.markers.keys()).collect();
+        dates.into_iter().copied()
+    }
+
+    /// The entries of a date in file order, empty if there are none
+    #[must_use]
+    pub fn entries_for(&self, date: NaiveDate) -> &[TimeEntry] {
+        self.entries.get(&date).map_or(&[], Vec::as_slice)
+    }
+
+    /// The vacation, sick or holiday marker of a date
+    #[must_use]
+    pub fn marker(&self, date: NaiveDate) -> Option<DayMarker> {
+        self.markers.get(&date).copied()
+    }
+
+    /// Marks a date as vacation, sick or holiday, or clears the marker with None.
+    /// Returns the previous marker
+    pub fn set_marker(&mut self, date: NaiveDate, marker: Option<DayMarker>) -> Option<DayMarker> {
+        match marker {
+            Some(m) => self.markers.insert(date, m),
+            None => self.markers.remove(&date),
+        }
+    }
+
+    /// Number of entries across all days. Markers aren't entries
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.values().map(Vec::len).sum()
+    }
+
+    /// True when there are no entries
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hours expected to be worked on a date given the length of a normal
+    /// workday. Vacation, sick days and holidays expect nothing, or half a
+    /// workday when marked as `half`
+    #[must_use]
+    pub fn expected_for(&self, date: NaiveDate, workday: Duration) -> Duration {
+        match self.markers.get(&date) {
+            Some(m) if m.half_day => workday / 2,
+            Some(_) => Duration::zero(),
+            None => workday,
+        }
+    }
+
+    /// Tracked minus expected time summed over every day in the file,
+    /// negative when less was tracked than expected
+    #[must_use]
+    pub fn overtime(&self, workday: Duration) -> Duration {
+        let mut overtime = self.total();
+        for date in self.entries.keys() {
+            overtime -= self.expected_for(*date, workday);
+        }
+
+        // Marked days without entries still count against expected time
+        for date in self.markers.keys() {
+            if !self.entries.contains_key(date) {
+                overtime -= self.expected_for(*date, workday);
+            }
+        }
+
+        overtime
+    }
+
+    /// Orders each day's entries by start time. Entries starting at the
+    /// same time keep their file order
+    pub fn sort(&mut self) {
+        for entries in self.entries.values_mut() {
+            entries.sort_by_key(|e| e.start);
+        }
+    }
+
+    /// A copy holding only the days in `range`, Ex. a `Week`, a `Month`
+    /// or `DateRange::new(from, to)`
+    #[must_use]
+    pub fn range<R: Into<DateRange>>(&self, range: R) -> Time {
+        let range = range.into();
+        if range.is_empty() {
+            return Time::new();
+        }
+
+        Time {
+            entries: self
+                .entries
+                .range(range)
+                .map(|(d, e)| (*d, e.clone()))
+                .collect(),
+            markers: self.markers.range(range).map(|(d, m)| (*d, *m)).collect(),
+        }
+    }
+
+    /// Combines the days of two files, Ex. one file per project. Entries
+    /// under the same date are joined and sorted by start time. When both
+    /// files mark the same day the marker from `self` is kept
+    #[must_use]
+    pub fn merge(mut self, other: Time) -> Time {
+        for (date, entries) in other.entries {
+            self.entries.entry(date).or_default().extend(entries);
+        }
+        for (date, marker) in other.markers {
+            self.markers.entry(date).or_insert(marker);
+        }
+
+        self.sort();
+        self
+    }
+
+    /// Every entry paired with its date, ordered by date and then by start
+    /// time within a day regardless of file order. Cancelled entries are
+    /// included
+    pub fn iter_entries(&self) -> impl Iterator<Item = (&NaiveDate, &TimeEntry)> {
+        self.entries.iter().flat_map(|(date, entries)| {
+            let mut day: Vec<&TimeEntry> = entries.iter().collect();
+            day.sort_by_key(|e| e.start);
+            day.into_iter().map(move |e| (date, e))
+        })
+    }
+
+    /// The entry with the latest date and start time, skipping cancelled
+    /// entries. Ex. the task to pick back up after a break
+    #[must_use]
+    pub fn latest_entry(&self) -> Option<&TimeEntry> {
+        self.iter_entries()
+            .map(|(_, e)| e)
+            .filter(|e| !e.cancelled)
+            .last()
+    }
+
+    /// Sum of every entry's duration, skipping cancelled entries
+    #[must_use]
+    pub fn total(&self) -> Duration {
+        self.entries.values().map(|e| sum_entries(e)).sum()
+    }
+
+    /// Sum of the entries on a single date
+    #[must_use]
+    pub fn total_for(&self, date: NaiveDate) -> Duration {
+        self.entries
+            .get(&date)
+            .map_or_else(Duration::zero, |e| sum_entries(e))
+    }
+
+    /// Sum of the entries on the days in `range`
+    #[must_use]
+    pub fn total_in_range<R: Into<DateRange>>(&self, range: R) -> Duration {
+        let range = range.into();
+        if range.is_empty() {
+            return Duration::zero();
+        }
 
-impl From<chrono::ParseError> for TimeError {
-    fn from(err: chrono::ParseError) -> TimeError {
-        TimeError::ParseError(err)
+        self.entries.range(range).map(|(_, e)| sum_entries(e)).sum()
     }
 }
 
-pub struct Time {
-    pub entries: HashMap<NaiveDate, Vec<TimeEntry>>,
+fn sum_entries(entries: &[TimeEntry]) -> Duration {
+    entries
+        .iter()
+        .filter(|e| !e.cancelled)
+        .map(TimeEntry::duration)
+        .sum()
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Time::new()
+    }
 }
 
 impl fmt::Display for Time {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut output = String::new();
-        for (date, entries) in &self.entries {
-            output.push_str(&format!("{}\n", date.format("%Y-%m-%d")));
+        // Days can have entries, a marker or both
+        for date in self.days() {
+            writeln!(f, "{}", date.format("%Y-%m-%d"))?;
 
-            for e in entries {
-                output.push_str(&format!("{}\n", e));
+            if let Some(m) = self.marker(date) {
+                writeln!(f, "{m}")?;
             }
+
+            for e in self.entries_for(date) {
+                writeln!(f, "{e}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Why a day isn't a regular working day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MarkerKind {
+    Vacation,
+    Sick,
+    /// A public holiday
+    Holiday,
+}
+
+/// A `VACATION`, `SICK` or `HOLIDAY` line under a date, optionally
+/// followed by `half` when only part of the day was taken off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DayMarker {
+    pub kind: MarkerKind,
+    pub half_day: bool,
+}
+
+impl DayMarker {
+    fn parse(line: &str) -> Option<DayMarker> {
+        let mut words = line.split_whitespace();
+        let kind = match words.next()? {
+            "VACATION" => MarkerKind::Vacation,
+            "SICK" => MarkerKind::Sick,
+            "HOLIDAY" => MarkerKind::Holiday,
+            _ => return None,
+        };
+        let half_day = match words.next() {
+            None => false,
+            Some(w) if w.eq_ignore_ascii_case("half") => true,
+            Some(_) => return None,
+        };
+        if words.next().is_some() {
+            return None;
         }
 
-        write!(f, "{}", output)
+        Some(DayMarker { kind, half_day })
+    }
+}
+
+impl fmt::Display for DayMarker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            MarkerKind::Vacation => write!(f, "VACATION")?,
+            MarkerKind::Sick => write!(f, "SICK")?,
+            MarkerKind::Holiday => write!(f, "HOLIDAY")?,
+        }
+        if self.half_day {
+            write!(f, " half")?;
+        }
+        Ok(())
     }
 }
 
 #[allow(dead_code)] // allow date
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TimeEntry {
     pub date: NaiveDate,
     pub start: NaiveTime,
-    pub end: NaiveTime,
-    pub duration: Duration,
+    /// None while the entry is still running, written as `--:--`
+    pub end: Option<NaiveTime>,
     pub description: String,
+    /// Entries prefixed with `x` are kept but don't count towards totals
+    pub cancelled: bool,
+}
+
+impl TimeEntry {
+    /// Parses a single entry line such as `03:00 04:00 Sketched ideas`
+    /// that belongs to the given date
+    ///
+    /// # Errors
+    /// Errors if the line isn't an entry or its start and end time
+    /// are malformed
+    pub fn parse_with_date(date: NaiveDate, line: &str) -> Result<TimeEntry, ParseError> {
+        match classify_line(line)? {
+            Line::Entry(e) => Ok(e.into_entry(date)),
+            _ => Err(ParseError::InvalidEntry(format!("not an entry: {line}"))),
+        }
+    }
+
+    /// Time spent on the entry. An end time before the start time means
+    /// the entry ran past midnight. Open entries have no duration yet,
+    /// use `duration_at` to measure them
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.end_datetime().map_or_else(Duration::zero, |end| {
+            end.signed_duration_since(self.start_datetime())
+        })
+    }
+
+    /// The entry's date and start time together
+    #[must_use]
+    pub fn start_datetime(&self) -> NaiveDateTime {
+        self.date.and_time(self.start)
+    }
+
+    /// The entry's date and end time together, on the next day when the
+    /// entry ran past midnight. None for open entries
+    #[must_use]
+    pub fn end_datetime(&self) -> Option<NaiveDateTime> {
+        let end = self.end?;
+        let date = if end < self.start {
+            self.date.succ_opt()?
+        } else {
+            self.date
+        };
+        Some(date.and_time(end))
+    }
+
+    /// Same as `duration` but an open entry counts as running until `now`
+    #[must_use]
+    pub fn duration_at(&self, now: NaiveDateTime) -> Duration {
+        if self.end.is_some() {
+            return self.duration();
+        }
+
+        let elapsed = now.signed_duration_since(self.start_datetime());
+        elapsed.max(Duration::zero())
+    }
+
+    /// True while the entry has no end time
+    #[must_use]
+    pub fn is_open(&self) -> bool {
+        self.end.is_none()
+    }
+
+    /// Location or context tokens in the description without their `@`,
+    /// Ex. `Standup @office` has the context `office`
+    #[must_use]
+    pub fn contexts(&self) -> Vec<&str> {
+        self.tokens('@').collect()
+    }
+
+    /// Tags in the description without their `#`,
+    /// Ex. `Contract review #legal` has the tag `legal`
+    #[must_use]
+    pub fn tags(&self) -> Vec<&str> {
+        self.tokens('#').collect()
+    }
+
+    /// Projects in the description without their `+`,
+    /// Ex. `Fixed login +website` belongs to the project `website`
+    #[must_use]
+    pub fn projects(&self) -> Vec<&str> {
+        self.tokens('+').collect()
+    }
+
+    /// `key:value` tokens at the end of the description,
+    /// Ex. `Fixed login ticket:JIRA-123 rate:95` has `ticket` and `rate`.
+    /// Tags, projects and contexts can be mixed in with them. Keys start
+    /// with a letter so times and URLs aren't mistaken for metadata, and
+    /// the last value wins when a key is repeated
+    #[must_use]
+    pub fn meta(&self) -> BTreeMap<&str, &str> {
+        let mut meta = BTreeMap::new();
+        let trailing = self.description.split_whitespace().rev().map_while(|word| {
+            if let Some(pair) = meta_token(word) {
+                Some(Some(pair))
+            } else if word.len() > 1 && word.starts_with(['#', '+', '@']) {
+                Some(None)
+            } else {
+                None
+            }
+        });
+
+        // Walking backwards, so keep the first value seen for each key
+        for (key, value) in trailing.flatten() {
+            meta.entry(key).or_insert(value);
+        }
+        meta
+    }
+
+    /// The description without its tags, projects, contexts and trailing
+    /// metadata, with whitespace collapsed, Ex. for grouping entries that
+    /// describe the same work
+    #[must_use]
+    pub fn plain_description(&self) -> String {
+        let words: Vec<&str> = self.description.split_whitespace().collect();
+        let is_label = |word: &str| word.len() > 1 && word.starts_with(['#', '+', '@']);
+        let trailing = words
+            .iter()
+            .rev()
+            .take_while(|w| is_label(w) || meta_token(w).is_some())
+            .count();
+
+        words[..words.len() - trailing]
+            .iter()
+            .filter(|w| !is_label(w))
+            .copied()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The time the work was expected to take from `est:` metadata,
+    /// Ex. `Wrote tests est:2h`. None without it or if it isn't a duration
+    #[must_use]
+    pub fn estimate(&self) -> Option<Duration> {
+        self.meta()
+            .get("est")
+            .and_then(|est| parse_duration(est).ok())
+    }
+
+    /// True for an entry with the same day, times and cancelled state whose
+    /// description only differs in case, spacing, trailing punctuation or
+    /// word order, Ex. the same entry imported from two tools
+    #[must_use]
+    pub fn is_near_duplicate(&self, other: &TimeEntry) -> bool {
+        let words = |e: &TimeEntry| {
+            let mut words: Vec<String> = e
+                .description
+                .split_whitespace()
+                .map(|w| w.trim_end_matches(|c: char| c.is_ascii_punctuation()))
+                .filter(|w| !w.is_empty())
+                .map(str::to_lowercase)
+                .collect();
+            words.sort();
+            words
+        };
+
+        self.date == other.date
+            && self.start == other.start
+            && self.end == other.end
+            && self.cancelled == other.cancelled
+            && words(self) == words(other)
+    }
+
+    /// Adds `text` to the end of the description but before its trailing
+    /// metadata, which would stop being metadata otherwise. Whitespace is
+    /// collapsed, Ex. `Fixed login ticket:JIRA-1` and `and logout` give
+    /// `Fixed login and logout ticket:JIRA-1`
+    pub fn append_description(&mut self, text: &str) {
+        let words: Vec<&str> = self.description.split_whitespace().collect();
+        let is_label = |word: &str| word.len() > 1 && word.starts_with(['#', '+', '@']);
+        let trailing = words
+            .iter()
+            .rev()
+            .take_while(|w| is_label(w) || meta_token(w).is_some())
+            .count();
+        let at = (words.len() - trailing..words.len())
+            .find(|i| meta_token(words[*i]).is_some())
+            .unwrap_or(words.len());
+
+        let mut appended = words[..at].to_vec();
+        appended.extend(text.split_whitespace());
+        appended.extend(&words[at..]);
+        self.description = appended.join(" ");
+    }
+
+    /// Words of the description starting with `sigil`, with the sigil and
+    /// any trailing punctuation removed
+    fn tokens(&self, sigil: char) -> impl Iterator<Item = &str> {
+        self.description.split_whitespace().filter_map(move |word| {
+            let token = word
+                .strip_prefix(sigil)?
+                .trim_end_matches(|c: char| c.is_ascii_punctuation());
+            if token.is_empty() {
+                None
+            } else {
+                Some(token)
+            }
+        })
+    }
+}
+
+/// Parses an entry line prefixed with its date,
+/// Ex. `1822-01-15 03:00 04:00 Sketched ideas`
+impl FromStr for TimeEntry {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (date, line) = s.split_at(s.find(' ').unwrap_or(s.len()));
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|source| ParseError::BadDate { line: None, source })?;
+        TimeEntry::parse_with_date(date, line.trim_start())
+    }
 }
 
 impl fmt::Display for TimeEntry {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{} {} {}",
-            self.start.format("%H:%M").to_string(),
-            self.end.format("%H:%M").to_string(),
-            self.description
-        )
+        if self.cancelled {
+            write!(f, "x ")?;
+        }
+        write!(f, "{} ", self.start.format("%H:%M"))?;
+        match self.end {
+            Some(end) => write!(f, "{}", end.format("%H:%M"))?,
+            None => write!(f, "{OPEN_END}")?,
+        }
+        write!(f, " {}", self.description)
+    }
+}
+
+fn meta_token(word: &str) -> Option<(&str, &str)> {
+    let (key, value) = word.split_once(':')?;
+    let valid_key = key.starts_with(|c: char| c.is_ascii_alphabetic())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if valid_key && !value.is_empty() && !value.starts_with("//") {
+        Some((key, value))
+    } else {
+        None
+    }
+}
+
+/// Parses a whole time.txt file, the same as `parse_time`
+impl FromStr for Time {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_time(s)
     }
 }
 
@@ -87,72 +582,142 @@ impl fmt::Display for TimeEntry {
 ///
 /// # Errors
 /// If the string doesn't fit the time.txt format it will error.
-/// Common reasons are incorrect date and time format
-pub fn parse_time(contents: &str) -> Result<Time, Box<dyn Error>> {
-    let mut t = Time {
-        entries: HashMap::new(),
-    };
+/// Common reasons are incorrect date and time format, or entries
+/// before the first date
+pub fn parse_time(contents: &str) -> Result<Time, ParseError> {
+    parse_time_with(contents, ParseOptions::default())
+}
+
+/// Settings for `parse_time_with`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions {
+    /// Sort each day's entries by start time instead of keeping file order
+    pub sort: bool,
+}
+
+/// Same as `parse_time` but with control over how the file is read
+///
+/// # Errors
+/// If the string doesn't fit the time.txt format it will error.
+pub fn parse_time_with(contents: &str, options: ParseOptions) -> Result<Time, ParseError> {
+    let mut t = Time::new();
 
     let mut date: Option<NaiveDate> = None;
-    for line in contents.lines() {
-        debug!("line {}", line);
-        // Ignore all lines that start with // as they are comments
-        // or if they are empty
-        if line.starts_with("//") || line.is_empty() {
-            continue;
-        }
-
-        // Time and date entries are longer than 9 characters
-        // Time 1:30 or 01:30, but always universal time
-        // Date 1970-01-01
-        if line.len() < 9 {
-            continue;
-        }
-
-        // Check if the line is a date to indicate the start of a
-        // date block
-        let d = NaiveDate::parse_from_str(line, "%Y-%m-%d").ok();
-        if let Some(d) = d {
-            date = Some(d);
-            continue; // found a date so proceed to next line
-        }
-
-        let (index, start_time, end_time) = find_time_range(line)?;
-        let desc = &line[index..line.len()];
-        let desc = desc.trim();
-
-        // Time uses a hash map to sort entries by date
-        date.and_then(|d| {
-            let entry = TimeEntry {
-                date: d,
-                start: start_time,
-                end: end_time,
-                duration: end_time.signed_duration_since(start_time),
-                description: desc.to_string(),
-            };
-
-            // Check to see if it has the date key
-            // if it doesn't add the key and create the
-            // entries Vec
-            let entries = t.entries.entry(d).or_insert_with(|| vec![]);
-            entries.push(entry);
-
-            Some(d)
-        });
+    for (i, line) in contents.lines().enumerate() {
+        debug!("line {line}");
+        let missing_date = || ParseError::MissingDateHeader { line: Some(i + 1) };
+        match classify_line(line).map_err(|err| err.at_line(i + 1))? {
+            Line::Ignored | Line::Skipped => {}
+            Line::Date(d) => date = Some(d),
+            Line::Marker(m) => {
+                let d = date.ok_or_else(missing_date)?;
+                t.markers.insert(d, m);
+            }
+            Line::Entry(e) => {
+                let d = date.ok_or_else(missing_date)?;
+                // Time uses a sorted map to keep entries in date order.
+                // Check to see if it has the date key
+                // if it doesn't add the key and create the
+                // entries Vec
+                t.entries.entry(d).or_default().push(e.into_entry(d));
+            }
+        }
+    }
+
+    if options.sort {
+        t.sort();
     }
 
     Ok(t)
 }
 
-fn find_time_range(line: &str) -> Result<(usize, NaiveTime, NaiveTime), TimeError> {
+/// A single line of a time.txt file as understood by the parser
+enum Line<'a> {
+    /// Comments and empty lines
+    Ignored,
+    /// Lines too short to hold a date or an entry
+    Skipped,
+    Date(NaiveDate),
+    Marker(DayMarker),
+    Entry(EntryLine<'a>),
+}
+
+/// The parts of an entry line before it is attached to a date
+struct EntryLine<'a> {
+    start: NaiveTime,
+    end: Option<NaiveTime>,
+    description: &'a str,
+    cancelled: bool,
+}
+
+impl EntryLine<'_> {
+    fn into_entry(self, date: NaiveDate) -> TimeEntry {
+        TimeEntry {
+            date,
+            start: self.start,
+            end: self.end,
+            description: self.description.to_string(),
+            cancelled: self.cancelled,
+        }
+    }
+}
+
+fn classify_line(line: &str) -> Result<Line<'_>, ParseError> {
+    // Ignore all lines that start with // as they are comments
+    // or if they are empty
+    if line.starts_with("//") || line.is_empty() {
+        return Ok(Line::Ignored);
+    }
+
+    // Day markers can be shorter than an entry so look for them first
+    if let Some(m) = DayMarker::parse(line) {
+        return Ok(Line::Marker(m));
+    }
+
+    // Time and date entries are longer than 9 characters
+    // Time 1:30 or 01:30, but always universal time
+    // Date 1970-01-01
+    if line.len() < 9 {
+        return Ok(Line::Skipped);
+    }
+
+    // Check if the line is a date to indicate the start of a
+    // date block
+    if let Ok(d) = NaiveDate::parse_from_str(line, "%Y-%m-%d") {
+        return Ok(Line::Date(d));
+    }
+
+    // A leading x marks the entry as cancelled, like a done task in todo.txt
+    let (cancelled, line) = match line.strip_prefix("x ") {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+
+    let (index, start, end) = find_time_range(line)?;
+    Ok(Line::Entry(EntryLine {
+        start,
+        end,
+        description: line[index..].trim(),
+        cancelled,
+    }))
+}
+
+/// Written in place of the end time while an entry is still running
+const OPEN_END: &str = "--:--";
+
+fn find_time_range(line: &str) -> Result<(usize, NaiveTime, Option<NaiveTime>), ParseError> {
+    let bad_time = |source| ParseError::BadTime { line: None, source };
     // The start date and end date are allows at the beginning of a line
     // and are separated by a space. Let's make sure we have two spaces
     let mut num_of_spaces = 0;
     let mut start_time: Option<NaiveTime> = None;
     let mut end_time: Option<NaiveTime> = None;
+    let mut open = false;
     let mut start_time_space = 0;
     let mut end_time_space = 0;
-    for (i, c) in line.chars().enumerate() {
+    // Byte offsets so the line can be sliced, text before the times can
+    // be anything
+    for (i, c) in line.char_indices() {
         if c == ' ' {
             num_of_spaces += 1;
 
@@ -161,12 +726,13 @@ fn find_time_range(line: &str) -> Result<(usize, NaiveTime, NaiveTime), TimeErro
                 // make sure i is greater than min
                 // min is H:MM (4 characters)
                 if i < 4 {
-                    return Err(TimeError::TimeNotFound("Start time not found".to_string()));
+                    return Err(ParseError::MissingStartTime { line: None });
                 }
 
                 // Make sure it's a valid time
                 start_time_space = i;
-                let st = NaiveTime::parse_from_str(&line[0..start_time_space], "%H:%M")?;
+                let st = NaiveTime::parse_from_str(&line[0..start_time_space], "%H:%M")
+                    .map_err(bad_time)?;
                 start_time = Some(st);
             }
 
@@ -175,14 +741,17 @@ fn find_time_range(line: &str) -> Result<(usize, NaiveTime, NaiveTime), TimeErro
                 // make sure i is greater than min
                 // min is H:MM H:MM (9 characters)
                 if i < 9 {
-                    return Err(TimeError::TimeNotFound("End time not found".to_string()));
+                    return Err(ParseError::MissingEndTime { line: None });
                 }
 
                 // Make sure it's a valid time
                 end_time_space = i;
-                let et =
-                    NaiveTime::parse_from_str(&line[start_time_space..end_time_space], "%H:%M")?;
-                end_time = Some(et);
+                let et = &line[start_time_space..end_time_space];
+                if et.trim() == OPEN_END {
+                    open = true;
+                } else {
+                    end_time = Some(NaiveTime::parse_from_str(et, "%H:%M").map_err(bad_time)?);
+                }
             }
 
             // After we found two spaces we "should" have both start and end time
@@ -195,31 +764,22 @@ fn find_time_range(line: &str) -> Result<(usize, NaiveTime, NaiveTime), TimeErro
     }
 
     // If we have less than two spaces then we know we didn't
-    // find a start or end date so continue
-    if num_of_spaces < 2 {
-        Err(TimeError::TimeNotFound(
-            "Neither start or end time not found".to_string(),
-        ))
-    } else {
-        let st: NaiveTime;
-        let et: NaiveTime;
-        match start_time {
-            Some(t) => st = t,
-            None => return Err(TimeError::TimeNotFound("Start time not found".to_string())),
-        }
-
-        match end_time {
-            Some(t) => et = t,
-            None => return Err(TimeError::TimeNotFound("End time not found".to_string())),
-        }
+    // find a start or end date
+    let Some(st) = start_time else {
+        return Err(ParseError::MissingStartTime { line: None });
+    };
 
-        Ok((end_time_space, st, et))
+    if num_of_spaces < 2 || (end_time.is_none() && !open) {
+        return Err(ParseError::MissingEndTime { line: None });
     }
+
+    Ok((end_time_space, st, end_time))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::error::Error;
 
     #[test]
     fn test_valid_lines() -> Result<(), Box<dyn Error>> {
@@ -238,11 +798,360 @@ mod tests {
         let t = parse_time(input);
         match t {
             Ok(t) => {
-                let result = format!("{}", t);
+                let result = format!("{t}");
                 assert_eq!(result, expected);
                 Ok(())
             }
-            Err(error) => Err(error),
+            Err(error) => Err(Box::new(error)),
         }
     }
+
+    #[test]
+    fn test_from_str() -> Result<(), Box<dyn Error>> {
+        let t: Time = "1822-01-15\n3:00 4:00 Sketched ideas\n".parse()?;
+        assert_eq!(format!("{t}"), "1822-01-15\n03:00 04:00 Sketched ideas\n");
+
+        let e: TimeEntry = "1822-01-15 3:00 4:00 Sketched ideas".parse()?;
+        assert_eq!(e.date, NaiveDate::from_ymd_opt(1822, 1, 15).unwrap());
+        assert_eq!(e.description, "Sketched ideas");
+        assert_eq!(format!("{e}"), "03:00 04:00 Sketched ideas");
+
+        assert!("3:00 4:00 Missing date".parse::<TimeEntry>().is_err());
+        assert!("1822-01-15 Not an entry line".parse::<TimeEntry>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_day_markers() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-14\n\
+        VACATION\n\
+        1822-01-15\n\
+        SICK half\n\
+        3:00 6:00 Sketched ideas\n\
+        1822-01-16\n\
+        9:00 17:00 Created the first computer\n\
+        1822-01-17\n\
+        HOLIDAY\n";
+
+        let t = parse_time(input)?;
+        let d = |day| NaiveDate::from_ymd_opt(1822, 1, day).unwrap();
+        let workday = Duration::hours(8);
+
+        assert_eq!(
+            t.marker(d(15)),
+            Some(DayMarker {
+                kind: MarkerKind::Sick,
+                half_day: true
+            })
+        );
+        assert_eq!(t.expected_for(d(14), workday), Duration::zero());
+        assert_eq!(t.expected_for(d(15), workday), Duration::hours(4));
+        assert_eq!(t.expected_for(d(16), workday), workday);
+        assert_eq!(t.marker(d(17)).map(|m| m.kind), Some(MarkerKind::Holiday));
+        assert_eq!(t.expected_for(d(17), workday), Duration::zero());
+        assert_eq!(t.overtime(workday), Duration::hours(-1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancelled_entries() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-15\n\
+        x 3:00 4:00 Sketched ideas\n\
+        9:00 17:00 Created the first computer\n";
+
+        let t = parse_time(input)?;
+        let d = NaiveDate::from_ymd_opt(1822, 1, 15).unwrap();
+        let entries = &t.entries[&d];
+
+        assert!(entries[0].cancelled);
+        assert!(!entries[1].cancelled);
+        assert_eq!(t.overtime(Duration::hours(8)), Duration::zero());
+        assert_eq!(
+            format!("{t}"),
+            "1822-01-15\nx 03:00 04:00 Sketched ideas\n09:00 17:00 Created the first computer\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_contexts() -> Result<(), Box<dyn Error>> {
+        let e: TimeEntry =
+            "1776-07-04 14:00 18:00 Signing @Philadelphia, then @home @ #USA".parse()?;
+        assert_eq!(e.contexts(), vec!["Philadelphia", "home"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_duration() -> Result<(), Box<dyn Error>> {
+        let e: TimeEntry = "1822-01-15 15:30 17:45 Decided on the name".parse()?;
+        assert_eq!(e.duration(), Duration::minutes(135));
+
+        let e: TimeEntry = "1822-01-15 23:00 1:30 Worked through the night".parse()?;
+        assert_eq!(e.duration(), Duration::minutes(150));
+
+        let e: TimeEntry = "1822-01-15 9:00 --:-- Still building".parse()?;
+        let now = NaiveDate::from_ymd_opt(1822, 1, 15)
+            .unwrap()
+            .and_hms_opt(10, 15, 0)
+            .unwrap();
+        assert!(e.is_open());
+        assert_eq!(e.duration(), Duration::zero());
+        assert_eq!(e.duration_at(now), Duration::minutes(75));
+        assert_eq!(format!("{e}"), "09:00 --:-- Still building");
+        Ok(())
+    }
+
+    #[test]
+    fn test_totals() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-14\n\
+        3:00 4:00 Sketched ideas\n\
+        x 4:00 5:00 Cancelled nap\n\
+        1822-01-15\n\
+        4:00 11:00 Created the first computer\n\
+        1822-01-16\n\
+        15:30 17:30 Decided on the name\n";
+
+        let t = parse_time(input)?;
+        let d = |day| NaiveDate::from_ymd_opt(1822, 1, day).unwrap();
+
+        assert_eq!(t.total(), Duration::hours(10));
+        assert_eq!(t.total_for(d(14)), Duration::hours(1));
+        assert_eq!(t.total_for(d(17)), Duration::zero());
+        assert_eq!(t.total_in_range(d(14)..=d(15)), Duration::hours(8));
+        Ok(())
+    }
+
+    #[test]
+    fn test_date_order() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-16\n\
+        15:30 17:30 Decided on the name\n\
+        1822-01-14\n\
+        VACATION\n\
+        1822-01-15\n\
+        4:00 11:00 Created the first computer\n";
+
+        let expected = "1822-01-14\n\
+        VACATION\n\
+        1822-01-15\n\
+        04:00 11:00 Created the first computer\n\
+        1822-01-16\n\
+        15:30 17:30 Decided on the name\n";
+
+        assert_eq!(format!("{}", parse_time(input)?), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-15\n\
+        15:30 17:30 Decided on the name\n\
+        3:00 4:00 Sketched ideas\n";
+
+        let expected = "1822-01-15\n\
+        03:00 04:00 Sketched ideas\n\
+        15:30 17:30 Decided on the name\n";
+
+        let mut t = parse_time(input)?;
+        assert_ne!(format!("{t}"), expected);
+        t.sort();
+        assert_eq!(format!("{t}"), expected);
+
+        let t = parse_time_with(input, ParseOptions { sort: true })?;
+        assert_eq!(format!("{t}"), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_entries() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-16\n\
+        15:30 17:30 Decided on the name\n\
+        1822-01-15\n\
+        9:00 11:00 Created the first computer\n\
+        3:00 4:00 Sketched ideas\n";
+
+        let t = parse_time(input)?;
+        let order: Vec<String> = t
+            .iter_entries()
+            .map(|(d, e)| format!("{d} {}", e.start.format("%H:%M")))
+            .collect();
+
+        assert_eq!(
+            order,
+            vec!["1822-01-15 03:00", "1822-01-15 09:00", "1822-01-16 15:30"]
+        );
+        assert_eq!(
+            t.latest_entry().map(|e| e.description.as_str()),
+            Some("Decided on the name")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_range() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-14\n\
+        3:00 4:00 Sketched ideas\n\
+        1822-01-15\n\
+        SICK half\n\
+        4:00 11:00 Created the first computer\n\
+        1822-01-16\n\
+        15:30 17:30 Decided on the name\n";
+
+        let t = parse_time(input)?;
+        let d = |day| NaiveDate::from_ymd_opt(1822, 1, day).unwrap();
+
+        let r = t.range(DateRange::new(d(15), d(16)));
+        assert_eq!(
+            format!("{r}"),
+            "1822-01-15\nSICK half\n04:00 11:00 Created the first computer\n\
+            1822-01-16\n15:30 17:30 Decided on the name\n"
+        );
+        assert_eq!(
+            t.range(DateRange::new(d(16), d(14))).total(),
+            Duration::zero()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge() -> Result<(), Box<dyn Error>> {
+        let work = parse_time("1822-01-15\n9:00 11:00 Created the first computer\n")?;
+        let home = parse_time("1822-01-15\n3:00 4:00 Sketched ideas\n1822-01-16\nVACATION\n")?;
+
+        let expected = "1822-01-15\n\
+        03:00 04:00 Sketched ideas\n\
+        09:00 11:00 Created the first computer\n\
+        1822-01-16\n\
+        VACATION\n";
+
+        assert_eq!(format!("{}", work.merge(home)), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |input: &str| parse_time(input).err().unwrap();
+
+        assert!(matches!(
+            err("1822-01-15\n25:00 26:00 Impossible\n"),
+            ParseError::BadTime { line: Some(2), .. }
+        ));
+        assert!(matches!(
+            err("1822-01-15\n\n12:00 Lunch\n"),
+            ParseError::MissingEndTime { line: Some(3) }
+        ));
+        assert!(matches!(
+            err("Not a date or an entry\n"),
+            ParseError::MissingStartTime { line: Some(1) }
+        ));
+        assert!(matches!(
+            err("// No date yet\n3:00 4:00 Sketched ideas\n"),
+            ParseError::MissingDateHeader { line: Some(2) }
+        ));
+        assert_eq!(
+            err("VACATION\n").to_string(),
+            "line 1: no date header before this line"
+        );
+    }
+
+    #[test]
+    fn test_derived_traits() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-15\n3:00 4:00 Sketched ideas\n";
+        let t = parse_time(input)?;
+        assert_eq!(t, t.clone());
+        assert_ne!(t, Time::new());
+
+        let e: TimeEntry = "1822-01-15 3:00 4:00 Sketched ideas".parse()?;
+        assert_eq!(t.entries.values().next().unwrap()[0], e);
+
+        let set: std::collections::HashSet<TimeEntry> = vec![e.clone(), e].into_iter().collect();
+        assert_eq!(set.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_accessors() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-15\n\
+        3:00 4:00 Sketched ideas\n\
+        9:00 11:00 Created the first computer\n\
+        1822-01-16\n\
+        VACATION\n";
+
+        let mut t = parse_time(input)?;
+        let d = |day| NaiveDate::from_ymd_opt(1822, 1, day).unwrap();
+
+        assert_eq!(t.days().collect::<Vec<_>>(), vec![d(15), d(16)]);
+        assert_eq!(t.entries_for(d(15)).len(), 2);
+        assert!(t.entries_for(d(16)).is_empty());
+        assert_eq!(t.len(), 2);
+        assert!(!t.is_empty());
+        assert!(Time::new().is_empty());
+
+        assert!(t.set_marker(d(16), None).is_some());
+        assert_eq!(t.days().count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_datetimes() -> Result<(), Box<dyn Error>> {
+        let d = NaiveDate::from_ymd_opt(1822, 1, 15).unwrap();
+        let at = |d: NaiveDate, h, m| d.and_hms_opt(h, m, 0).unwrap();
+
+        let e: TimeEntry = "1822-01-15 23:00 1:30 Worked through the night".parse()?;
+        assert_eq!(e.start_datetime(), at(d, 23, 0));
+        assert_eq!(e.end_datetime(), Some(at(d.succ_opt().unwrap(), 1, 30)));
+
+        let e: TimeEntry = "1822-01-15 9:00 --:-- Still building".parse()?;
+        assert_eq!(e.end_datetime(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_near_duplicate() -> Result<(), Box<dyn Error>> {
+        let e: TimeEntry = "2024-01-01 9:00 10:00 Code review #backend".parse()?;
+        let near =
+            |line: &str| -> Result<bool, ParseError> { Ok(e.is_near_duplicate(&line.parse()?)) };
+        assert!(near("2024-01-01 9:00 10:00 Code review #backend")?);
+        assert!(near("2024-01-01 9:00 10:00 #backend  code Review.")?);
+        assert!(!near("2024-01-01 9:00 10:30 Code review #backend")?);
+        assert!(!near("2024-01-02 9:00 10:00 Code review #backend")?);
+        assert!(!near("2024-01-01 x 9:00 10:00 Code review #backend")?);
+        assert!(!near("2024-01-01 9:00 10:00 Code review")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_meta() -> Result<(), Box<dyn Error>> {
+        let e: TimeEntry =
+            "2024-01-01 9:00 10:00 Fixed login at https://x.io note:ok ticket:JIRA-1 #backend rate:95 rate:90"
+                .parse()?;
+        let meta: Vec<_> = e.meta().into_iter().collect();
+        assert_eq!(
+            meta,
+            vec![("note", "ok"), ("rate", "90"), ("ticket", "JIRA-1")]
+        );
+        assert_eq!(e.plain_description(), "Fixed login at https://x.io");
+
+        assert_eq!(e.estimate(), None);
+        let e: TimeEntry = "2024-01-01 9:00 10:00 Tests est:1h30m #backend".parse()?;
+        assert_eq!(e.estimate(), Some(Duration::minutes(90)));
+        let e: TimeEntry = "2024-01-01 9:00 10:00 Tests est:soon".parse()?;
+        assert_eq!(e.estimate(), None);
+        let e: TimeEntry = "2024-01-01 9:00 10:00 Tests est:999999999999999:00".parse()?;
+        assert_eq!(e.estimate(), None);
+
+        let mut e: TimeEntry = "2024-01-01 9:00 10:00 Call at 10:30 with re: budget".parse()?;
+        assert!(e.meta().is_empty());
+        e.append_description(" and  more ");
+        assert_eq!(e.description, "Call at 10:30 with re: budget and more");
+
+        let mut e: TimeEntry =
+            "2024-01-01 9:00 10:00 Fixed login #web ticket:JIRA-1 #bug".parse()?;
+        e.append_description("and logout");
+        assert_eq!(
+            e.description,
+            "Fixed login #web and logout ticket:JIRA-1 #bug"
+        );
+        assert_eq!(e.meta().get("ticket"), Some(&"JIRA-1"));
+        Ok(())
+    }
 }
@@ -1,32 +1,310 @@
 #![warn(rust_2018_idioms)]
+use chrono::{NaiveDate, Weekday};
+use chrono_tz::Tz;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
 use std::env;
-use std::fs;
-use timetxt::Time;
+use std::error::Error;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use timetxt::DateRange;
 
-fn main() {
-    let mut args = env::args();
-    args.next(); // Skip program name arg
+mod commands;
 
-    let filename = match args.next() {
-        Some(arg) => arg,
-        None => panic!("Must provide filename"),
-    };
+const EXIT_STATUS: &str = "Exit status:
+  0  Success
+  1  A file couldn't be read, parsed or written, or has problems
+  2  Invalid arguments";
+
+/// Track time in a plain text time.txt file
+#[derive(Debug, Parser)]
+#[command(version, about, after_help = EXIT_STATUS)]
+struct Cli {
+    /// The time.txt file to use, can be repeated for commands that only
+    /// read [default: $TIMETXT_FILE, the configured file or time.txt]
+    #[arg(short, long, global = true)]
+    file: Vec<PathBuf>,
+
+    /// Use the file of a workspace from the config, can be repeated and
+    /// `all` reads every workspace
+    #[arg(short, long, global = true, value_name = "NAME")]
+    workspace: Vec<String>,
+
+    /// Only use days from this date on, as YYYY-MM-DD or a period like
+    /// 2024-W07 to start from its first day
+    #[arg(long, global = true, conflicts_with = "period")]
+    from: Option<String>,
+
+    /// Only use days up to this date, as YYYY-MM-DD or a period like
+    /// 2024-W09 to end with its last day
+    #[arg(long, global = true, conflicts_with = "period")]
+    to: Option<String>,
+
+    /// Only use days in a period, Ex. today, yesterday, this-week,
+    /// last-week, this-month, last-month, this-year, last-year, 2024,
+    /// 2024-Q1, 2024-07, 2024-W07 or 2024-07-04
+    #[arg(long, global = true)]
+    period: Option<String>,
+
+    /// How reports are printed [default: table]
+    #[arg(long, global = true, value_enum)]
+    format: Option<commands::table::Format>,
+
+    /// Print reports in the stable tab separated format for scripts, the
+    /// same as --format porcelain
+    #[arg(long, global = true, conflicts_with = "format")]
+    porcelain: bool,
+
+    /// Read defaults from this file instead of
+    /// ~/.config/timetxt/config.toml
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Show times in this zone, Ex. America/New_York or UTC. Files are
+    /// read as local time unless they start with a comment like
+    /// `// timezone: Europe/Berlin`, which edits write in too
+    #[arg(long, global = true, value_name = "ZONE", value_parser = parse_timezone)]
+    timezone: Option<Tz>,
+
+    /// The first day of weeks, Ex. monday or sunday [default: the
+    /// configured week_start or monday]
+    #[arg(long, global = true, value_name = "DAY", value_parser = parse_weekday)]
+    week_start: Option<Weekday>,
+
+    /// Don't color the output. Colors are also left out when `NO_COLOR`
+    /// is set or the output isn't a terminal
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Shorthand for `timetxt report FILE...`
+    #[arg(value_name = "FILE")]
+    legacy_files: Vec<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
 
-    // Read file
-    let contents = fs::read_to_string(filename).expect("Something went wrong reading the file");
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print the total of every day, week or month and a grand total
+    Report(commands::report::ReportArgs),
+    /// Add an entry without touching the rest of the file
+    Add(commands::add::AddArgs),
+    /// Start an open entry at the current time, stopping any running one
+    Start(commands::start::StartArgs),
+    /// Set the end of the running entry to the current time
+    Stop,
+    /// Stop the running entry, or start one if none is running
+    Punch(commands::punch::PunchArgs),
+    /// Change the description, times or tags of the latest entry
+    Amend(commands::amend::AmendArgs),
+    /// Add text to the description of the running entry
+    Note(commands::note::NoteArgs),
+    /// Start a new entry for the task of the latest one
+    Continue,
+    /// Remove the running entry, for a timer started by mistake
+    Cancel,
+    /// Show today's entries and the total so far
+    Today(commands::Inputs),
+    /// Print the running entry and today's total on one line, for prompts
+    Status(commands::Inputs),
+    /// Show a week's entries by day with daily and weekly totals
+    Week(commands::week::WeekArgs),
+    /// Total the time by tag, project or description
+    Summary(commands::summary::SummaryArgs),
+    /// List every entry in order with its labels and a running total
+    Log(commands::Inputs),
+    /// Print the entries whose description matches a pattern
+    Search(commands::search::SearchArgs),
+    /// List untracked time between entries and untracked weekdays
+    Gaps(commands::gaps::GapsArgs),
+    /// List entries whose times overlap on the same day
+    Overlaps(commands::overlaps::OverlapsArgs),
+    /// Compare est: estimates with the time tracked per task or project
+    Estimates(commands::estimates::EstimatesArgs),
+    /// Add the recurring entries from the config's templates to a day or week
+    Fill(commands::fill::FillArgs),
+    /// Show progress towards the daily or weekly goal and when today's share is done
+    Goal(commands::goal::GoalArgs),
+    /// Show totals, averages, records and the current streak
+    Stats(commands::Inputs),
+    /// List every tag with its number of entries and total
+    Tags(commands::Inputs),
+    /// List every project with its entries, first and last day and total
+    Projects(commands::Inputs),
+    /// Split the totals per day or project into billable and non-billable
+    Billable(commands::billable::BillableArgs),
+    /// Print a month's hours per day and project as a grid
+    Timesheet(commands::timesheet::TimesheetArgs),
+    /// Print an itemized statement with rounded hours and amounts
+    Invoice(commands::invoice::InvoiceArgs),
+    /// Print every day and entry as JSON, entries as CSV for spreadsheets
+    /// or intervals for Timewarrior
+    Export(commands::export::ExportArgs),
+    /// Check the file for problems, exiting with 1 if any are found
+    Validate(commands::Inputs),
+    /// Check files for common problems and say how to fix them
+    Doctor(commands::Inputs),
+    /// Rewrite the file in canonical form, keeping comments
+    Fmt(commands::fmt::FmtArgs),
+    /// Put dates and entries in order, leaving the lines as they are
+    Sort(commands::fmt::FmtArgs),
+    /// Combine files into one, reporting duplicate and overlapping entries
+    Merge(commands::merge::MergeArgs),
+    /// Print the man page, or write one per command with --dir
+    Man(commands::man::ManArgs),
+    /// Convert another tool's export into time.txt entries
+    Import(commands::import::ImportArgs),
+    /// Remove entries that repeat another of their day, Ex. after importing twice
+    Dedupe(commands::dedupe::DedupeArgs),
+    /// Move old days into a time-archive-YYYY.txt file per year
+    Archive(commands::archive::ArchiveArgs),
+    /// Split old days into a file per year or month that the file includes
+    Rotate(commands::rotate::RotateArgs),
+    /// Open the file in $EDITOR at today's entries
+    Edit,
+    /// Keep a report on screen, printing it again when the file changes
+    Watch(commands::watch::WatchArgs),
+    /// Browse weeks and start or stop the timer in a terminal interface
+    #[cfg(feature = "tui")]
+    Tui,
+}
 
-    let t: Time = timetxt::parse_time(&contents).expect("Failed to parse");
+fn main() -> ExitCode {
+    // Stop quietly when the reader goes away, Ex. `timetxt report | head`,
+    // instead of panicking on the next print
+    #[cfg(unix)]
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
 
-    // Get the total time of all entries
-    for (date, entries) in &t.entries {
-        println!("{}", date);
-        for e in entries {
-            println!(
-                "{:0>#2}:{:0>#2}",
-                e.duration.num_hours(),
-                e.duration.num_minutes() - e.duration.num_hours() * 60,
-            );
+    // Clap prints its own errors and exits with 2
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) if e.is::<commands::UsageError>() => {
+            Cli::command().error(ErrorKind::ValueValidation, e).exit()
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
         }
     }
-    println!("{}", t);
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let mut files = cli.file;
+    files.extend(cli.legacy_files);
+    let config = commands::config::Config::load(cli.config.as_deref())?;
+    files.extend(config.workspace_files(&cli.workspace)?);
+    let week_start = cli.week_start.or(config.week_start).unwrap_or(Weekday::Mon);
+    let today = commands::now_in(cli.timezone).date();
+    let period = |period: &str| {
+        DateRange::parse_period_with(period, today, week_start)
+            .map_err(|e| commands::UsageError(e.to_string()))
+    };
+    let range = match &cli.period {
+        Some(p) => Some(period(p)?),
+        None if cli.from.is_some() || cli.to.is_some() => Some(DateRange::new(
+            match &cli.from {
+                Some(from) => period(from)?.start(),
+                None => NaiveDate::MIN,
+            },
+            match &cli.to {
+                Some(to) => period(to)?.end(),
+                None => NaiveDate::MAX,
+            },
+        )),
+        None => None,
+    };
+    let format = if cli.porcelain {
+        commands::table::Format::Porcelain
+    } else {
+        cli.format.unwrap_or_default()
+    };
+    let color = !cli.no_color
+        && env::var_os("NO_COLOR").is_none()
+        && io::stdout().is_terminal()
+        && format == commands::table::Format::Table;
+    let global = commands::Global {
+        files,
+        default_file: env::var_os("TIMETXT_FILE")
+            .filter(|file| !file.is_empty())
+            .map(|file| commands::expand_home(Path::new(&file)))
+            .or_else(|| config.file.clone())
+            .unwrap_or_else(|| PathBuf::from(commands::DEFAULT_FILE)),
+        range,
+        output: commands::table::Output { format, color },
+        week_start,
+        fiscal_year_start: config.fiscal_year_start.unwrap_or(1),
+        rounding: config.rounding(),
+        workday: config.expected_hours,
+        hourly_rate: config.hourly_rate,
+        date_format: config.date_format.unwrap_or_else(|| "%Y-%m-%d".to_string()),
+        goal: config.goal,
+        billable_projects: config.billable_projects,
+        clients: config.clients,
+        invoice: config.invoice,
+        templates: config.templates,
+        holidays: config.holidays,
+        timezone: cli.timezone,
+    };
+
+    let command = cli
+        .command
+        .unwrap_or_else(|| Command::Report(commands::report::ReportArgs::default()));
+    match command {
+        Command::Report(args) => commands::report::run(&global, &args),
+        Command::Add(args) => commands::add::run(&global.single_file()?, args),
+        Command::Start(args) => commands::start::run(&global.single_file()?, args),
+        Command::Stop => commands::stop::run(&global.single_file()?),
+        Command::Punch(args) => commands::punch::run(&global.single_file()?, &args),
+        Command::Amend(args) => commands::amend::run(&global.single_file()?, &args),
+        Command::Note(args) => commands::note::run(&global.single_file()?, &args),
+        Command::Continue => commands::resume::run(&global.single_file()?),
+        Command::Cancel => commands::cancel::run(&global.single_file()?),
+        Command::Today(inputs) => commands::today::run(&global, &inputs),
+        Command::Status(inputs) => commands::status::run(&global, &inputs),
+        Command::Week(args) => commands::week::run(&global, &args),
+        Command::Summary(args) => commands::summary::run(&global, &args),
+        Command::Log(inputs) => commands::log::run(&global, &inputs),
+        Command::Search(args) => commands::search::run(&global, &args),
+        Command::Gaps(args) => commands::gaps::run(&global, &args),
+        Command::Overlaps(args) => commands::overlaps::run(&global, &args),
+        Command::Estimates(args) => commands::estimates::run(&global, &args),
+        Command::Fill(args) => commands::fill::run(&global, &args),
+        Command::Goal(args) => commands::goal::run(&global, &args),
+        Command::Stats(inputs) => commands::stats::run(&global, &inputs),
+        Command::Tags(inputs) => commands::tags::run(&global, &inputs),
+        Command::Projects(inputs) => commands::projects::run(&global, &inputs),
+        Command::Billable(args) => commands::billable::run(&global, &args),
+        Command::Timesheet(args) => commands::timesheet::run(&global, &args),
+        Command::Invoice(args) => commands::invoice::run(&global, &args),
+        Command::Export(args) => commands::export::run(&global, &args),
+        Command::Validate(inputs) => commands::validate::run(&global, &inputs),
+        Command::Doctor(inputs) => commands::doctor::run(&global, &inputs),
+        Command::Fmt(args) => commands::fmt::run(&global, &args),
+        Command::Sort(args) => commands::sort::run(&global, &args),
+        Command::Merge(args) => commands::merge::run(&global, &args),
+        Command::Man(args) => commands::man::run(Cli::command(), &args),
+        Command::Import(args) => commands::import::run(&global, &args),
+        Command::Dedupe(args) => commands::dedupe::run(&global.single_file()?, &args),
+        Command::Archive(args) => commands::archive::run(&global.single_file()?, &args),
+        Command::Rotate(args) => commands::rotate::run(&global.single_file()?, &args),
+        Command::Edit => commands::edit::run(&global.single_file()?),
+        Command::Watch(args) => commands::watch::run(&global, &args),
+        #[cfg(feature = "tui")]
+        Command::Tui => commands::tui::run(&global),
+    }
+}
+
+fn parse_weekday(s: &str) -> Result<Weekday, String> {
+    s.parse()
+        .map_err(|_| format!("{s:?} isn't a day of the week"))
+}
+
+fn parse_timezone(s: &str) -> Result<Tz, String> {
+    s.parse()
+        .map_err(|_| format!("{s:?} isn't a time zone like Europe/Berlin or UTC"))
 }
@@ -0,0 +1,109 @@
+use crate::{Time, TimeEntry};
+use chrono::NaiveDate;
+
+/// What `Time::normalize` changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NormalizeReport {
+    /// Days whose entries weren't in start time order
+    pub days_sorted: Vec<NaiveDate>,
+    /// Entries whose description had extra whitespace
+    pub descriptions_trimmed: usize,
+    /// Entries removed because an identical one was on the same day
+    pub duplicates_removed: usize,
+}
+
+impl NormalizeReport {
+    /// True when the Time was already normalized
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.days_sorted.is_empty()
+            && self.descriptions_trimmed == 0
+            && self.duplicates_removed == 0
+    }
+}
+
+impl Time {
+    /// Cleans up the entries so `to_time_txt` writes a tidy file: each
+    /// day's entries are sorted by start time, descriptions lose
+    /// leading, trailing and repeated whitespace and exact duplicate
+    /// entries are removed. Dates are always kept in order, repeated date
+    /// blocks are merged when parsing and times are zero padded when
+    /// written, so those never need fixing here
+    pub fn normalize(&mut self) -> NormalizeReport {
+        let mut report = NormalizeReport::default();
+
+        for (date, entries) in &mut self.entries {
+            for e in entries.iter_mut() {
+                let tidy = e
+                    .description
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if tidy != e.description {
+                    e.description = tidy;
+                    report.descriptions_trimmed += 1;
+                }
+            }
+
+            let mut unique: Vec<TimeEntry> = Vec::with_capacity(entries.len());
+            for e in entries.drain(..) {
+                if unique.contains(&e) {
+                    report.duplicates_removed += 1;
+                } else {
+                    unique.push(e);
+                }
+            }
+
+            if !unique.windows(2).all(|w| w[0].start <= w[1].start) {
+                unique.sort_by_key(|e| e.start);
+                report.days_sorted.push(*date);
+            }
+            *entries = unique;
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_time, TimeEntry};
+    use chrono::NaiveDate;
+    use std::error::Error;
+
+    #[test]
+    fn test_normalize() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-15\n\
+        15:30 17:30 Decided   on the name\n\
+        3:00 4:00 Sketched ideas\n\
+        1822-01-16\n\
+        9:00 10:00 Told everyone\n\
+        1822-01-15\n\
+        3:00 4:00 Sketched ideas\n";
+
+        let mut t = parse_time(input)?;
+        let mut untidy: TimeEntry = "1822-01-16 11:00 12:00 Lunch".parse()?;
+        untidy.description = " Lunch ".to_string();
+        t.add_entry(untidy)?;
+
+        let report = t.normalize();
+        assert_eq!(
+            report.days_sorted,
+            vec![NaiveDate::from_ymd_opt(1822, 1, 15).unwrap()]
+        );
+        assert_eq!(report.descriptions_trimmed, 2);
+        assert_eq!(report.duplicates_removed, 1);
+        assert_eq!(
+            t.to_time_txt(),
+            "1822-01-15\n\
+            03:00 04:00 Sketched ideas\n\
+            15:30 17:30 Decided on the name\n\
+            \n\
+            1822-01-16\n\
+            09:00 10:00 Told everyone\n\
+            11:00 12:00 Lunch\n"
+        );
+        assert!(t.normalize().is_empty());
+        Ok(())
+    }
+}
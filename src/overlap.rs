@@ -0,0 +1,88 @@
+use crate::{Time, TimeEntry};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+/// Two entries on the same day whose time ranges intersect
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overlap<'a> {
+    pub date: NaiveDate,
+    /// The entry that comes first in the file
+    pub first: &'a TimeEntry,
+    pub second: &'a TimeEntry,
+    /// Where the overlapping span starts and ends, the end can be on
+    /// the next day for entries that run past midnight
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+impl Overlap<'_> {
+    /// How much time is counted twice
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.end.signed_duration_since(self.start)
+    }
+}
+
+impl Time {
+    /// Every pair of entries on the same day that overlap, Ex. time that
+    /// would be billed twice. Open and cancelled entries are ignored
+    #[must_use]
+    pub fn overlaps(&self) -> Vec<Overlap<'_>> {
+        let mut overlaps = Vec::new();
+        for date in self.days() {
+            let entries: Vec<(&TimeEntry, NaiveDateTime)> = self
+                .entries_for(date)
+                .iter()
+                .filter(|e| !e.cancelled)
+                .filter_map(|e| Some((e, e.end_datetime()?)))
+                .collect();
+
+            for (i, &(first, first_end)) in entries.iter().enumerate() {
+                for &(second, second_end) in &entries[i + 1..] {
+                    let start = first.start_datetime().max(second.start_datetime());
+                    let end = first_end.min(second_end);
+
+                    if start < end {
+                        overlaps.push(Overlap {
+                            date,
+                            first,
+                            second,
+                            start,
+                            end,
+                        });
+                    }
+                }
+            }
+        }
+
+        overlaps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_time;
+    use chrono::Duration;
+    use std::error::Error;
+
+    #[test]
+    fn test_overlaps() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-15\n\
+        3:00 5:00 Sketched ideas\n\
+        4:30 6:00 Created the first computer\n\
+        6:00 7:00 Back to back is fine\n\
+        x 3:00 7:00 Cancelled entries don't count\n\
+        22:00 1:00 Worked through the night\n\
+        23:00 23:30 Midnight snack\n";
+
+        let t = parse_time(input)?;
+        let overlaps = t.overlaps();
+
+        assert_eq!(overlaps.len(), 2);
+        assert_eq!(overlaps[0].first.description, "Sketched ideas");
+        assert_eq!(overlaps[0].second.description, "Created the first computer");
+        assert_eq!(overlaps[0].duration(), Duration::minutes(30));
+        assert_eq!(overlaps[1].second.description, "Midnight snack");
+        assert_eq!(overlaps[1].duration(), Duration::minutes(30));
+        Ok(())
+    }
+}
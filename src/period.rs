@@ -0,0 +1,419 @@
+use crate::ParseError;
+use chrono::{Datelike, Duration, IsoWeek, NaiveDate, Weekday};
+use std::fmt;
+use std::ops::{Bound, RangeBounds, RangeInclusive};
+
+/// Days from `start` to `end`, both included. A range that starts after
+/// it ends is empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateRange {
+    start: NaiveDate,
+    end: NaiveDate,
+}
+
+impl DateRange {
+    #[must_use]
+    pub fn new(start: NaiveDate, end: NaiveDate) -> DateRange {
+        DateRange { start, end }
+    }
+
+    /// A range holding a single day
+    #[must_use]
+    pub fn day(date: NaiveDate) -> DateRange {
+        DateRange::new(date, date)
+    }
+
+    #[must_use]
+    pub fn start(&self) -> NaiveDate {
+        self.start
+    }
+
+    #[must_use]
+    pub fn end(&self) -> NaiveDate {
+        self.end
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.start > self.end
+    }
+
+    #[must_use]
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+
+    /// Parses a period name into the days it covers. Relative names are
+    /// taken from `today`
+    /// - `today`, `yesterday`
+    /// - `this-week`, `last-week` for Monday to Sunday weeks
+    /// - `this-month`, `last-month`, `this-year`, `last-year`
+    /// - `2024`, `2024-Q1`, `2024-07`, `2024-W07` and `2024-07-04`
+    ///
+    /// # Errors
+    /// Errors with `ParseError::InvalidPeriod` if the name isn't one of those
+    pub fn parse_period(period: &str, today: NaiveDate) -> Result<DateRange, ParseError> {
+        DateRange::parse_period_with(period, today, Weekday::Mon)
+    }
+
+    /// Like `parse_period` with weeks starting on `week_start`. `2024-W07`
+    /// is then the week sharing most of its days with the ISO week
+    ///
+    /// # Errors
+    /// Errors with `ParseError::InvalidPeriod` for unknown names
+    pub fn parse_period_with(
+        period: &str,
+        today: NaiveDate,
+        week_start: Weekday,
+    ) -> Result<DateRange, ParseError> {
+        let this_week = Week::starting_on(today, week_start);
+        let this_month = Month::of(today);
+        let range = match period.to_lowercase().as_str() {
+            "today" => DateRange::day(today),
+            "yesterday" => DateRange::day(today - Duration::days(1)),
+            "this-week" => this_week.into(),
+            "last-week" => this_week.pred().into(),
+            "this-month" => this_month.into(),
+            "last-month" => this_month.pred().into(),
+            "this-year" => year(today.year()),
+            "last-year" => year(today.year() - 1),
+            _ => parse_named_period(period, week_start)
+                .ok_or_else(|| ParseError::InvalidPeriod(period.to_string()))?,
+        };
+        Ok(range)
+    }
+
+    /// Every date in the range in calendar order
+    pub fn iter_days(&self) -> impl Iterator<Item = NaiveDate> {
+        let end = self.end;
+        self.start.iter_days().take_while(move |d| *d <= end)
+    }
+}
+
+/// Every day of `year`. Out of range years give an empty range
+fn year(year: i32) -> DateRange {
+    match (
+        NaiveDate::from_ymd_opt(year, 1, 1),
+        NaiveDate::from_ymd_opt(year, 12, 31),
+    ) {
+        (Some(start), Some(end)) => DateRange::new(start, end),
+        _ => DateRange::new(NaiveDate::MAX, NaiveDate::MIN),
+    }
+}
+
+/// `2024`, `2024-Q1`, `2024-07`, `2024-W07` or `2024-07-04`
+fn parse_named_period(period: &str, week_start: Weekday) -> Option<DateRange> {
+    if let Ok(date) = NaiveDate::parse_from_str(period, "%Y-%m-%d") {
+        return Some(DateRange::day(date));
+    }
+
+    let (y, rest) = match period.split_once('-') {
+        Some((y, rest)) => (y.parse().ok()?, Some(rest)),
+        None => (period.parse().ok()?, None),
+    };
+    let Some(rest) = rest else {
+        return Some(year(y));
+    };
+
+    if let Some(q) = rest.strip_prefix(['Q', 'q']) {
+        let q: u32 = q.parse().ok().filter(|q| (1..=4).contains(q))?;
+        let first = Month::of(NaiveDate::from_ymd_opt(y, q * 3 - 2, 1)?);
+        return Some(DateRange::new(first.start(), first.succ().succ().end()));
+    }
+    if let Some(w) = rest.strip_prefix(['W', 'w']) {
+        let week = Week::from_iso(y, w.parse().ok()?)?;
+        return Some(week.with_start(week_start).into());
+    }
+    let month = NaiveDate::from_ymd_opt(y, rest.parse().ok()?, 1)?;
+    Some(Month::of(month).into())
+}
+
+impl RangeBounds<NaiveDate> for DateRange {
+    fn start_bound(&self) -> Bound<&NaiveDate> {
+        Bound::Included(&self.start)
+    }
+
+    fn end_bound(&self) -> Bound<&NaiveDate> {
+        Bound::Included(&self.end)
+    }
+}
+
+impl From<RangeInclusive<NaiveDate>> for DateRange {
+    fn from(range: RangeInclusive<NaiveDate>) -> DateRange {
+        DateRange::new(*range.start(), *range.end())
+    }
+}
+
+impl fmt::Display for DateRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}..{}",
+            self.start.format("%Y-%m-%d"),
+            self.end.format("%Y-%m-%d")
+        )
+    }
+}
+
+/// Seven days starting on any weekday. `Week::containing` gives ISO weeks,
+/// which start on Monday
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Week {
+    start: NaiveDate,
+}
+
+impl Week {
+    /// The Monday to Sunday week `date` falls in
+    #[must_use]
+    pub fn containing(date: NaiveDate) -> Week {
+        Week::starting_on(date, Weekday::Mon)
+    }
+
+    /// The week `date` falls in for weeks starting on `week_start`,
+    /// Ex. `Weekday::Sun`
+    #[must_use]
+    pub fn starting_on(date: NaiveDate, week_start: Weekday) -> Week {
+        let offset =
+            (7 + date.weekday().num_days_from_monday() - week_start.num_days_from_monday()) % 7;
+        Week {
+            start: date - Duration::days(i64::from(offset)),
+        }
+    }
+
+    /// An ISO week by year and week number, Ex. `Week::from_iso(2024, 7)`
+    /// for 2024-W07. None if the year doesn't have that week
+    #[must_use]
+    pub fn from_iso(year: i32, week: u32) -> Option<Week> {
+        NaiveDate::from_isoywd_opt(year, week, Weekday::Mon).map(|start| Week { start })
+    }
+
+    #[must_use]
+    pub fn start(&self) -> NaiveDate {
+        self.start
+    }
+
+    #[must_use]
+    pub fn end(&self) -> NaiveDate {
+        self.start + Duration::days(6)
+    }
+
+    /// The ISO year and week number most of the week's days fall in,
+    /// that of its first day for weeks starting on Monday
+    #[must_use]
+    pub fn iso_week(&self) -> IsoWeek {
+        (self.start + Duration::days(3)).iso_week()
+    }
+
+    /// The week starting on `week_start` that shares most of its days with
+    /// this one, and so has the same ISO week
+    #[must_use]
+    pub fn with_start(&self, week_start: Weekday) -> Week {
+        Week::starting_on(self.start + Duration::days(3), week_start)
+    }
+
+    /// The week after this one
+    #[must_use]
+    pub fn succ(&self) -> Week {
+        Week {
+            start: self.start + Duration::days(7),
+        }
+    }
+
+    /// The week before this one
+    #[must_use]
+    pub fn pred(&self) -> Week {
+        Week {
+            start: self.start - Duration::days(7),
+        }
+    }
+
+    #[must_use]
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        DateRange::from(*self).contains(date)
+    }
+
+    pub fn iter_days(&self) -> impl Iterator<Item = NaiveDate> {
+        DateRange::from(*self).iter_days()
+    }
+}
+
+/// The ISO week and the days, Ex. `2024-W07 Mon 12 Feb – Sun 18 Feb`
+impl fmt::Display for Week {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let iso = self.iso_week();
+        write!(
+            f,
+            "{}-W{:02} {} – {}",
+            iso.year(),
+            iso.week(),
+            self.start.format("%a %d %b"),
+            self.end().format("%a %d %b")
+        )
+    }
+}
+
+impl From<Week> for DateRange {
+    fn from(week: Week) -> DateRange {
+        DateRange::new(week.start(), week.end())
+    }
+}
+
+/// A calendar month
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Month {
+    first: NaiveDate,
+}
+
+impl Month {
+    /// The month `date` falls in
+    #[must_use]
+    pub fn of(date: NaiveDate) -> Month {
+        Month {
+            first: date - Duration::days(i64::from(date.day0())),
+        }
+    }
+
+    #[must_use]
+    pub fn year(&self) -> i32 {
+        self.first.year()
+    }
+
+    /// Month number starting at 1 for January
+    #[must_use]
+    pub fn month(&self) -> u32 {
+        self.first.month()
+    }
+
+    /// The first day of the month
+    #[must_use]
+    pub fn start(&self) -> NaiveDate {
+        self.first
+    }
+
+    /// The last day of the month
+    #[must_use]
+    pub fn end(&self) -> NaiveDate {
+        self.succ().first - Duration::days(1)
+    }
+
+    /// The month after this one
+    #[must_use]
+    pub fn succ(&self) -> Month {
+        // Every month has fewer than 32 days so this always lands in the next one
+        Month::of(self.first + Duration::days(32))
+    }
+
+    /// The month before this one
+    #[must_use]
+    pub fn pred(&self) -> Month {
+        Month::of(self.first - Duration::days(1))
+    }
+
+    #[must_use]
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        Month::of(date) == *self
+    }
+
+    pub fn iter_days(&self) -> impl Iterator<Item = NaiveDate> {
+        DateRange::from(*self).iter_days()
+    }
+}
+
+impl From<Month> for DateRange {
+    fn from(month: Month) -> DateRange {
+        DateRange::new(month.start(), month.end())
+    }
+}
+
+impl fmt::Display for Month {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.first.format("%Y-%m"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_periods() {
+        let d = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+
+        // 2024-01-03 is a Wednesday
+        let week = Week::containing(d(2024, 1, 3));
+        assert_eq!(week.start(), d(2024, 1, 1));
+        assert_eq!(week.end(), d(2024, 1, 7));
+        assert_eq!(week.iso_week().week(), 1);
+        assert_eq!(week.iter_days().count(), 7);
+        assert_eq!(week.pred().start(), d(2023, 12, 25));
+        assert_eq!(Week::from_iso(2024, 1), Some(week));
+        assert_eq!(Week::from_iso(2024, 53), None);
+        let sunday = Week::starting_on(d(2024, 1, 3), Weekday::Sun);
+        assert_eq!(sunday.start(), d(2023, 12, 31));
+        assert_eq!(sunday.iso_week().week(), 1);
+        assert_eq!(week.with_start(Weekday::Sun), sunday);
+        assert_eq!(sunday.with_start(Weekday::Mon), week);
+        assert_eq!(week.to_string(), "2024-W01 Mon 01 Jan – Sun 07 Jan");
+        assert_eq!(sunday.to_string(), "2024-W01 Sun 31 Dec – Sat 06 Jan");
+
+        let month = Month::of(d(2024, 2, 14));
+        assert_eq!(month.start(), d(2024, 2, 1));
+        assert_eq!(month.end(), d(2024, 2, 29));
+        assert_eq!(month.iter_days().count(), 29);
+        assert!(month.contains(d(2024, 2, 29)));
+        assert!(!month.contains(d(2024, 3, 1)));
+        assert_eq!(Month::of(d(2023, 12, 31)).succ(), Month::of(d(2024, 1, 1)));
+        assert_eq!(month.to_string(), "2024-02");
+
+        let range = DateRange::new(d(2024, 1, 5), d(2024, 1, 3));
+        assert!(range.is_empty());
+        assert_eq!(range.iter_days().count(), 0);
+        assert!(DateRange::day(d(2024, 1, 5)).contains(d(2024, 1, 5)));
+    }
+
+    #[test]
+    fn test_parse_period() -> Result<(), ParseError> {
+        let d = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        let today = d(2024, 3, 14);
+        let period = |s| DateRange::parse_period(s, today);
+
+        assert_eq!(period("yesterday")?, DateRange::day(d(2024, 3, 13)));
+        assert_eq!(
+            period("last-week")?,
+            DateRange::new(d(2024, 3, 4), d(2024, 3, 10))
+        );
+        assert_eq!(
+            period("last-month")?,
+            DateRange::new(d(2024, 2, 1), d(2024, 2, 29))
+        );
+        assert_eq!(
+            period("2023")?,
+            DateRange::new(d(2023, 1, 1), d(2023, 12, 31))
+        );
+        assert_eq!(
+            period("2024-Q4")?,
+            DateRange::new(d(2024, 10, 1), d(2024, 12, 31))
+        );
+        assert_eq!(
+            period("2024-07")?,
+            DateRange::new(d(2024, 7, 1), d(2024, 7, 31))
+        );
+        assert_eq!(period("2024-W01")?, Week::containing(d(2024, 1, 1)).into());
+        assert_eq!(period("2024-07-04")?, DateRange::day(d(2024, 7, 4)));
+
+        let sunday = |s| DateRange::parse_period_with(s, today, Weekday::Sun);
+        assert_eq!(
+            sunday("this-week")?,
+            DateRange::new(d(2024, 3, 10), d(2024, 3, 16))
+        );
+        assert_eq!(
+            sunday("2024-W01")?,
+            DateRange::new(d(2023, 12, 31), d(2024, 1, 6))
+        );
+
+        for bad in ["soon", "2024-Q5", "2024-13", "2024-W54", "24-07-04x"] {
+            assert_eq!(period(bad), Err(ParseError::InvalidPeriod(bad.to_string())));
+        }
+        Ok(())
+    }
+}
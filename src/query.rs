@@ -0,0 +1,324 @@
+use crate::{ParseError, Time, TimeEntry};
+use chrono::NaiveDate;
+use std::str::FromStr;
+
+/// A parsed filter expression such as
+/// `tag:backend AND date>=2024-01-01 AND desc~review`
+///
+/// Terms are
+/// - `tag:NAME`, `project:NAME` and `context:NAME` for `#`, `+` and `@` labels
+/// - `date=DATE` and `date` with `>=`, `<=`, `>` or `<`
+/// - `desc~TEXT` for descriptions containing `TEXT`, `desc=TEXT` for an exact match
+/// - `is:open` and `is:cancelled`
+///
+/// Terms are joined with `AND`, `OR` and `NOT` and grouped with parentheses.
+/// Terms next to each other without an operator are joined with `AND`.
+/// Text comparisons ignore case and values with spaces can be quoted,
+/// Ex. `desc~"code review"`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Term(Term),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    Tag(String),
+    Project(String),
+    Context(String),
+    Date(Cmp, NaiveDate),
+    DescContains(String),
+    DescEquals(String),
+    Open,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cmp {
+    Eq,
+    Ge,
+    Le,
+    Gt,
+    Lt,
+}
+
+impl Query {
+    /// # Errors
+    /// Errors with `ParseError::InvalidQuery` if the query doesn't follow
+    /// the syntax above
+    pub fn parse(query: &str) -> Result<Query, ParseError> {
+        let tokens = tokenize(query)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+
+        match parser.tokens.get(parser.pos) {
+            None => Ok(Query { expr }),
+            Some(t) => Err(invalid(format!("unexpected {t:?}"))),
+        }
+    }
+
+    /// True if the entry passes the filter
+    #[must_use]
+    pub fn matches(&self, entry: &TimeEntry) -> bool {
+        self.expr.matches(entry)
+    }
+}
+
+impl FromStr for Query {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Query::parse(s)
+    }
+}
+
+impl Time {
+    /// A copy holding only the entries matching a `Query` expression
+    ///
+    /// # Errors
+    /// Errors if the query can't be parsed
+    pub fn query(&self, query: &str) -> Result<Time, ParseError> {
+        let query = Query::parse(query)?;
+        Ok(self.filter(|e| query.matches(e)))
+    }
+}
+
+impl Expr {
+    fn matches(&self, e: &TimeEntry) -> bool {
+        match *self {
+            Expr::And(ref a, ref b) => a.matches(e) && b.matches(e),
+            Expr::Or(ref a, ref b) => a.matches(e) || b.matches(e),
+            Expr::Not(ref a) => !a.matches(e),
+            Expr::Term(ref t) => t.matches(e),
+        }
+    }
+}
+
+impl Term {
+    fn matches(&self, e: &TimeEntry) -> bool {
+        let has =
+            |labels: Vec<&str>, name: &str| labels.iter().any(|l| l.eq_ignore_ascii_case(name));
+
+        match *self {
+            Term::Tag(ref name) => has(e.tags(), name),
+            Term::Project(ref name) => has(e.projects(), name),
+            Term::Context(ref name) => has(e.contexts(), name),
+            Term::Date(cmp, date) => match cmp {
+                Cmp::Eq => e.date == date,
+                Cmp::Ge => e.date >= date,
+                Cmp::Le => e.date <= date,
+                Cmp::Gt => e.date > date,
+                Cmp::Lt => e.date < date,
+            },
+            Term::DescContains(ref text) => e.description.to_lowercase().contains(text),
+            Term::DescEquals(ref text) => e.description.to_lowercase() == *text,
+            Term::Open => e.is_open(),
+            Term::Cancelled => e.cancelled,
+        }
+    }
+
+    fn parse(token: &str) -> Result<Term, ParseError> {
+        // Two character operators have to be checked first
+        let ops = [
+            (">=", Some(Cmp::Ge)),
+            ("<=", Some(Cmp::Le)),
+            ("=", Some(Cmp::Eq)),
+            (":", Some(Cmp::Eq)),
+            (">", Some(Cmp::Gt)),
+            ("<", Some(Cmp::Lt)),
+            ("~", None),
+        ];
+        let found = ops
+            .iter()
+            .filter_map(|&(op, cmp)| token.find(op).map(|i| (i, op, cmp)))
+            .min_by_key(|&(i, op, _)| (i, std::cmp::Reverse(op.len())));
+        let Some((i, op, cmp)) = found else {
+            return Err(invalid(format!("{token:?} has no operator")));
+        };
+
+        let key = token[..i].to_lowercase();
+        let value = &token[i + op.len()..];
+        if value.is_empty() {
+            return Err(invalid(format!("{token:?} has no value")));
+        }
+
+        let term = match (key.as_str(), op) {
+            ("tag", ":" | "=") => Term::Tag(value.trim_start_matches('#').to_string()),
+            ("project", ":" | "=") => Term::Project(value.trim_start_matches('+').to_string()),
+            ("context", ":" | "=") => Term::Context(value.trim_start_matches('@').to_string()),
+            ("date", _) if op != "~" => {
+                let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .map_err(|_| invalid(format!("{value:?} isn't a YYYY-MM-DD date")))?;
+                Term::Date(cmp.unwrap_or(Cmp::Eq), date)
+            }
+            ("desc", "~") => Term::DescContains(value.to_lowercase()),
+            ("desc", ":" | "=") => Term::DescEquals(value.to_lowercase()),
+            ("is", ":" | "=") if value.eq_ignore_ascii_case("open") => Term::Open,
+            ("is", ":" | "=") if value.eq_ignore_ascii_case("cancelled") => Term::Cancelled,
+            _ => return Err(invalid(format!("unknown term {token:?}"))),
+        };
+        Ok(term)
+    }
+}
+
+fn invalid(message: String) -> ParseError {
+    ParseError::InvalidQuery(message)
+}
+
+/// Splits a query into words and parentheses, keeping quoted text together
+fn tokenize(query: &str) -> Result<Vec<String>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut chars = query.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(c) => current.push(c),
+                    None => return Err(invalid("missing closing quote".to_string())),
+                }
+            },
+            '(' | ')' => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+                tokens.push(c.to_string());
+            }
+            c if c.is_whitespace() => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        self.tokens
+            .get(self.pos)
+            .is_some_and(|t| t.eq_ignore_ascii_case(keyword))
+    }
+
+    fn or(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.and()?;
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.unary()?;
+        loop {
+            if self.peek_keyword("and") {
+                self.pos += 1;
+            } else if self.pos >= self.tokens.len()
+                || self.peek_keyword("or")
+                || self.peek_keyword(")")
+            {
+                return Ok(expr);
+            }
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        let Some(token) = self.tokens.get(self.pos).cloned() else {
+            return Err(invalid("query ended early".to_string()));
+        };
+        self.pos += 1;
+
+        if token.eq_ignore_ascii_case("not") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+
+        if token == "(" {
+            let expr = self.or()?;
+            if !self.peek_keyword(")") {
+                return Err(invalid("missing closing parenthesis".to_string()));
+            }
+            self.pos += 1;
+            return Ok(expr);
+        }
+
+        Ok(Expr::Term(Term::parse(&token)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_time;
+    use chrono::Duration;
+    use std::error::Error;
+
+    const INPUT: &str = "2024-01-01\n\
+        9:00 10:00 Code review #backend +api\n\
+        10:00 12:00 Planning #frontend\n\
+        2024-01-02\n\
+        9:00 11:00 Reviewed deploy scripts #backend @home\n\
+        x 11:00 12:00 Cancelled review #backend\n";
+
+    #[test]
+    fn test_query() -> Result<(), Box<dyn Error>> {
+        let t = parse_time(INPUT)?;
+        let total = |q: &str| -> Result<Duration, ParseError> { Ok(t.query(q)?.total()) };
+
+        assert_eq!(
+            total("tag:backend AND date>=2024-01-02 AND desc~review")?,
+            Duration::hours(2)
+        );
+        assert_eq!(total("tag:backend")?, Duration::hours(3));
+        assert_eq!(total("tag:frontend OR project:api")?, Duration::hours(3));
+        assert_eq!(total("NOT tag:backend")?, Duration::hours(2));
+        assert_eq!(
+            total("(context:home OR date=2024-01-01) tag:BACKEND")?,
+            Duration::hours(3)
+        );
+        assert_eq!(total("desc~\"code review\"")?, Duration::hours(1));
+        assert_eq!(t.query("is:cancelled")?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_query() {
+        for q in [
+            "",
+            "tag",
+            "tag:",
+            "date>=yesterday",
+            "(tag:a",
+            "desc~\"open",
+            "size:big",
+            "tag:a OR",
+        ] {
+            assert!(
+                matches!(Query::parse(q), Err(ParseError::InvalidQuery(_))),
+                "{:?} should not parse",
+                q
+            );
+        }
+    }
+}
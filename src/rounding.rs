@@ -0,0 +1,149 @@
+use crate::{sum_entries, Time};
+use chrono::{Duration, NaiveDate};
+
+/// Which way `Rounding` moves a duration that isn't a multiple of its step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoundingMode {
+    /// To the closest step, halfway rounds up
+    Nearest,
+    Up,
+    Down,
+}
+
+/// Rounds durations to a multiple of a step for billing,
+/// Ex. `Rounding::nearest(Duration::minutes(15))` for quarter hours
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rounding {
+    pub step: Duration,
+    pub mode: RoundingMode,
+}
+
+/// Whether rounding is applied to every entry or to each day's total
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoundPer {
+    Entry,
+    Day,
+}
+
+impl Rounding {
+    #[must_use]
+    pub fn nearest(step: Duration) -> Rounding {
+        Rounding {
+            step,
+            mode: RoundingMode::Nearest,
+        }
+    }
+
+    #[must_use]
+    pub fn up(step: Duration) -> Rounding {
+        Rounding {
+            step,
+            mode: RoundingMode::Up,
+        }
+    }
+
+    #[must_use]
+    pub fn down(step: Duration) -> Rounding {
+        Rounding {
+            step,
+            mode: RoundingMode::Down,
+        }
+    }
+
+    /// Rounds `d` to a multiple of the step. A step of zero or less
+    /// leaves `d` unchanged
+    #[must_use]
+    pub fn round(&self, d: Duration) -> Duration {
+        let step = self.step.num_seconds();
+        if step <= 0 {
+            return d;
+        }
+
+        let secs = d.num_seconds();
+        let down = secs.div_euclid(step) * step;
+        let rest = secs - down;
+        let round_up = match self.mode {
+            RoundingMode::Down => false,
+            RoundingMode::Up => rest > 0,
+            RoundingMode::Nearest => rest * 2 >= step,
+        };
+
+        Duration::seconds(if round_up { down + step } else { down })
+    }
+}
+
+impl Time {
+    /// Same as `total` with every entry or every day rounded first.
+    /// Cancelled entries are skipped
+    #[must_use]
+    pub fn rounded_total(&self, rounding: Rounding, per: RoundPer) -> Duration {
+        self.days()
+            .map(|date| self.rounded_total_for(date, rounding, per))
+            .sum()
+    }
+
+    /// Same as `total_for` with the entries or the day rounded first
+    #[must_use]
+    pub fn rounded_total_for(
+        &self,
+        date: NaiveDate,
+        rounding: Rounding,
+        per: RoundPer,
+    ) -> Duration {
+        let entries = self.entries_for(date);
+        match per {
+            RoundPer::Day => rounding.round(sum_entries(entries)),
+            RoundPer::Entry => entries
+                .iter()
+                .filter(|e| !e.cancelled)
+                .map(|e| rounding.round(e.duration()))
+                .sum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_time;
+    use std::error::Error;
+
+    #[test]
+    fn test_round() {
+        let quarter = Duration::minutes(15);
+        let m = Duration::minutes;
+
+        assert_eq!(Rounding::nearest(quarter).round(m(37)), m(30));
+        assert_eq!(Rounding::nearest(quarter).round(m(38)), m(45));
+        assert_eq!(Rounding::up(Duration::minutes(6)).round(m(61)), m(66));
+        assert_eq!(Rounding::up(quarter).round(m(60)), m(60));
+        assert_eq!(Rounding::down(quarter).round(m(59)), m(45));
+        assert_eq!(Rounding::up(Duration::zero()).round(m(59)), m(59));
+    }
+
+    #[test]
+    fn test_rounded_total() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-15\n\
+        3:00 3:10 Sketched ideas\n\
+        4:00 4:10 Created the first computer\n\
+        1822-01-16\n\
+        15:30 15:35 Decided on the name\n";
+
+        let t = parse_time(input)?;
+        let up = Rounding::up(Duration::minutes(15));
+
+        assert_eq!(t.rounded_total(up, RoundPer::Entry), Duration::minutes(45));
+        assert_eq!(t.rounded_total(up, RoundPer::Day), Duration::minutes(45));
+
+        let nearest = Rounding::nearest(Duration::minutes(15));
+        assert_eq!(
+            t.rounded_total(nearest, RoundPer::Entry),
+            Duration::minutes(30)
+        );
+        assert_eq!(
+            t.rounded_total(nearest, RoundPer::Day),
+            Duration::minutes(15)
+        );
+        Ok(())
+    }
+}
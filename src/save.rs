@@ -0,0 +1,129 @@
+use crate::Time;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Settings for `Time::save_with` and `write_atomic`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SaveOptions {
+    /// Copy the current file to `<path>.bak` before replacing it
+    pub backup: bool,
+}
+
+impl Time {
+    /// Writes the canonical time.txt form to `path` without ever leaving
+    /// a half written file behind. Comments in the original are not kept
+    ///
+    /// # Errors
+    /// Errors if the file or its temporary copy can't be written
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.save_with(path, SaveOptions::default())
+    }
+
+    /// Same as `save` with control over backups
+    ///
+    /// # Errors
+    /// Errors if the file, its backup or its temporary copy can't be written
+    pub fn save_with<P: AsRef<Path>>(&self, path: P, options: SaveOptions) -> io::Result<()> {
+        write_atomic(path.as_ref(), &self.to_time_txt(), options)
+    }
+}
+
+/// Replaces the file at `path` with `contents` by writing a temporary file
+/// next to it and renaming it over the original, so a crash mid write
+/// can't truncate the file
+///
+/// # Errors
+/// Errors if the temporary file can't be written or renamed, or the
+/// backup can't be made
+pub fn write_atomic(path: &Path, contents: &str, options: SaveOptions) -> io::Result<()> {
+    let tmp = sibling(path, "tmp");
+
+    let result = (|| {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&tmp, metadata.permissions())?;
+            if options.backup {
+                fs::copy(path, sibling(path, "bak"))?;
+            }
+        }
+
+        fs::rename(&tmp, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// `path` with `.extension` added, Ex. time.txt becomes time.txt.bak
+fn sibling(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_time;
+    use std::error::Error;
+
+    #[test]
+    fn test_save() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!("timetxt-save-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("time.txt");
+        fs::write(&path, "// Old file\n")?;
+
+        let t = parse_time("1822-01-15\n3:00 4:00 Sketched ideas\n")?;
+        t.save_with(&path, SaveOptions { backup: true })?;
+
+        assert_eq!(fs::read_to_string(&path)?, t.to_time_txt());
+        assert_eq!(
+            fs::read_to_string(dir.join("time.txt.bak"))?,
+            "// Old file\n"
+        );
+        assert!(!dir.join("time.txt.tmp").exists());
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_atomic() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!("timetxt-write-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("time.txt");
+
+        // A missing file is created
+        write_atomic(&path, "1822-01-15\r\n", SaveOptions::default())?;
+        assert_eq!(fs::read_to_string(&path)?, "1822-01-15\r\n");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o640))?;
+            write_atomic(&path, "1822-01-16\n", SaveOptions::default())?;
+            let mode = fs::metadata(&path)?.permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+        write_atomic(&path, "1822-01-17\n", SaveOptions::default())?;
+        assert_eq!(fs::read_to_string(&path)?, "1822-01-17\n");
+        assert!(!dir.join("time.txt.bak").exists());
+        assert!(!dir.join("time.txt.tmp").exists());
+
+        // Nothing is left behind when the file can't be written
+        let missing = dir.join("missing").join("time.txt");
+        assert!(write_atomic(&missing, "", SaveOptions::default()).is_err());
+        assert!(!dir.join("missing").exists());
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use crate::Time;
+use std::fmt::Write as _;
+use std::io;
+
+impl Time {
+    /// The canonical time.txt form of the file. Unlike `Display` each
+    /// day's entries are sorted by start time and date blocks are
+    /// separated by an empty line, so regenerating a file after edits
+    /// always produces the same output
+    #[must_use]
+    pub fn to_time_txt(&self) -> String {
+        let mut out = String::new();
+        for (i, date) in self.days().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            // Writing to a String never fails
+            let _ = writeln!(out, "{}", date.format("%Y-%m-%d"));
+
+            if let Some(m) = self.marker(date) {
+                let _ = writeln!(out, "{m}");
+            }
+
+            let mut entries: Vec<_> = self.entries_for(date).iter().collect();
+            entries.sort_by_key(|e| e.start);
+            for e in entries {
+                let _ = writeln!(out, "{e}");
+            }
+        }
+
+        out
+    }
+
+    /// Writes `to_time_txt` to `w`
+    ///
+    /// # Errors
+    /// Errors if writing to `w` fails
+    pub fn write_to<W: io::Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(self.to_time_txt().as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_time;
+    use std::error::Error;
+
+    #[test]
+    fn test_to_time_txt() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-16\n\
+        VACATION\n\
+        // Comments are dropped\n\
+        1822-01-15\n\
+        15:30 17:30 Decided on the name   \n\
+        3:00 4:00 Sketched ideas\n";
+
+        let expected = "1822-01-15\n\
+        03:00 04:00 Sketched ideas\n\
+        15:30 17:30 Decided on the name\n\
+        \n\
+        1822-01-16\n\
+        VACATION\n";
+
+        let t = parse_time(input)?;
+        assert_eq!(t.to_time_txt(), expected);
+
+        let mut out = Vec::new();
+        t.write_to(&mut out)?;
+        assert_eq!(String::from_utf8(out)?, expected);
+
+        // The canonical form parses back to the same thing
+        assert_eq!(parse_time(expected)?.to_time_txt(), expected);
+        Ok(())
+    }
+}
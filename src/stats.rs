@@ -0,0 +1,209 @@
+use crate::{DateRange, Time, TimeEntry};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+
+/// Summary numbers for a `Time`, see `Time::stats`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stats<'a> {
+    pub total: Duration,
+    /// Number of entries that aren't cancelled
+    pub entries: usize,
+    /// Number of days with at least one entry that isn't cancelled
+    pub days_tracked: usize,
+    /// Weekdays from the first day to the last without a marker, the days
+    /// time is expected on
+    pub working_days: usize,
+    /// Total divided by the working days, time tracked on other days
+    /// counts towards the total all the same
+    pub average_working_day: Duration,
+    pub longest_entry: Option<&'a TimeEntry>,
+    pub shortest_entry: Option<&'a TimeEntry>,
+    /// The day with the most tracked time and its total
+    pub busiest_day: Option<(NaiveDate, Duration)>,
+    /// Number of distinct `+project` labels
+    pub projects: usize,
+    /// Number of distinct `#tag` labels
+    pub tags: usize,
+}
+
+impl Time {
+    /// Aggregate numbers for dashboards and summaries. Cancelled entries
+    /// are skipped and open entries don't count as longest or shortest
+    #[must_use]
+    pub fn stats(&self) -> Stats<'_> {
+        let active = || self.iter_entries().map(|(_, e)| e).filter(|e| !e.cancelled);
+        let closed = || active().filter(|e| !e.is_open());
+
+        let mut busiest_day: Option<(NaiveDate, Duration)> = None;
+        let mut days_tracked = 0;
+        for date in self.days() {
+            if !self.entries_for(date).iter().any(|e| !e.cancelled) {
+                continue;
+            }
+            days_tracked += 1;
+
+            let total = self.total_for(date);
+            if busiest_day.is_none_or(|(_, most)| total > most) {
+                busiest_day = Some((date, total));
+            }
+        }
+
+        let working_days = match (self.days().next(), self.days().last()) {
+            (Some(first), Some(last)) => DateRange::new(first, last)
+                .iter_days()
+                .filter(|d| !matches!(d.weekday(), Weekday::Sat | Weekday::Sun))
+                .filter(|d| self.marker(*d).is_none())
+                .count(),
+            _ => 0,
+        };
+        let total = self.total();
+        let average_working_day = match i32::try_from(working_days) {
+            Ok(days) if days > 0 => total / days,
+            _ => Duration::zero(),
+        };
+
+        Stats {
+            total,
+            entries: active().count(),
+            days_tracked,
+            working_days,
+            average_working_day,
+            // Ties go to the earliest entry
+            longest_entry: closed().reduce(|a, b| if b.duration() > a.duration() { b } else { a }),
+            shortest_entry: closed().reduce(|a, b| if b.duration() < a.duration() { b } else { a }),
+            busiest_day,
+            projects: active()
+                .flat_map(TimeEntry::projects)
+                .collect::<BTreeSet<_>>()
+                .len(),
+            tags: active()
+                .flat_map(TimeEntry::tags)
+                .collect::<BTreeSet<_>>()
+                .len(),
+        }
+    }
+
+    /// How many days in a row up to `today` have tracked time. Today
+    /// doesn't break the streak before it's tracked, and weekends and
+    /// marked days are skipped unless they were tracked
+    #[must_use]
+    pub fn streak(&self, today: NaiveDate) -> usize {
+        let tracked = |date| self.entries_for(date).iter().any(|e| !e.cancelled);
+        let skipped = |date: NaiveDate| {
+            matches!(date.weekday(), Weekday::Sat | Weekday::Sun) || self.marker(date).is_some()
+        };
+
+        let mut streak = 0;
+        let mut date = today;
+        if !tracked(date) {
+            date = date.pred_opt().unwrap_or(date);
+        }
+        while self.days().next().is_some_and(|first| date >= first) {
+            if tracked(date) {
+                streak += 1;
+            } else if !skipped(date) {
+                break;
+            }
+            match date.pred_opt() {
+                Some(previous) => date = previous,
+                None => break,
+            }
+        }
+
+        streak
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_time, Time};
+    use chrono::{Duration, NaiveDate};
+    use std::error::Error;
+
+    #[test]
+    fn test_stats() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-15\n\
+        3:00 4:00 Sketched ideas\n\
+        4:00 11:00 Created the first computer\n\
+        1822-01-16\n\
+        15:30 17:30 Decided on the name\n\
+        18:00 --:-- Still going\n\
+        1822-01-17\n\
+        x 9:00 10:00 Cancelled\n";
+
+        let t = parse_time(input)?;
+        let stats = t.stats();
+
+        assert_eq!(stats.total, Duration::hours(10));
+        assert_eq!(stats.entries, 4);
+        assert_eq!(stats.days_tracked, 2);
+        assert_eq!(stats.working_days, 3);
+        assert_eq!(stats.average_working_day, Duration::minutes(200));
+        assert_eq!(
+            stats.longest_entry.map(|e| e.description.as_str()),
+            Some("Created the first computer")
+        );
+        assert_eq!(
+            stats.shortest_entry.map(|e| e.description.as_str()),
+            Some("Sketched ideas")
+        );
+        assert_eq!(
+            stats.busiest_day,
+            Some((
+                NaiveDate::from_ymd_opt(1822, 1, 15).unwrap(),
+                Duration::hours(8)
+            ))
+        );
+
+        assert_eq!(stats.projects, 0);
+        assert_eq!(stats.tags, 0);
+
+        let empty = Time::new();
+        assert_eq!(empty.stats().average_working_day, Duration::zero());
+        assert!(empty.stats().longest_entry.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_stats_working_days() -> Result<(), Box<dyn Error>> {
+        // A sick Friday and a weekend between two tracked days
+        let input = "1822-01-18\n\
+        SICK\n\
+        1822-01-19\n\
+        3:00 5:00 Sketched ideas\n\
+        1822-01-21\n\
+        3:00 7:00 Sketched more\n";
+
+        let t = parse_time(input)?;
+        let stats = t.stats();
+        assert_eq!(stats.days_tracked, 2);
+        assert_eq!(stats.working_days, 1);
+        assert_eq!(stats.average_working_day, Duration::hours(6));
+        Ok(())
+    }
+
+    #[test]
+    fn test_streak() -> Result<(), Box<dyn Error>> {
+        // 1822-01-18 is a Friday
+        let input = "1822-01-15\n\
+        3:00 4:00 Sketched ideas\n\
+        1822-01-17\n\
+        3:00 4:00 Sketched ideas\n\
+        1822-01-18\n\
+        4:00 11:00 Created the first computer\n\
+        1822-01-21\n\
+        VACATION\n\
+        1822-01-22\n\
+        15:30 17:30 Decided on the name\n";
+
+        let t = parse_time(input)?;
+        let d = |day| NaiveDate::from_ymd_opt(1822, 1, day).unwrap();
+        assert_eq!(t.streak(d(22)), 3);
+        assert_eq!(t.streak(d(23)), 3);
+        assert_eq!(t.streak(d(24)), 0);
+        assert_eq!(t.streak(d(16)), 1);
+        assert_eq!(Time::new().streak(d(16)), 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,843 @@
+use crate::edit::check_entry;
+use crate::{classify_line, Line, ParseError, TimeEntry, OPEN_END};
+use chrono::{NaiveDate, NaiveTime};
+
+/// Adds `entry` to the raw contents of a time.txt file and returns the new
+/// contents. Everything else, comments, blank lines and line endings
+/// included, is left as it was. The entry goes at the end of its date's
+/// block, a new block is created before the first later date or at the end
+/// of the file when the date isn't in the file yet
+#[must_use]
+pub fn insert_entry(contents: &str, entry: &TimeEntry) -> String {
+    let (contents, last) = insert_date(contents, entry.date);
+    let (mut lines, eol) = split_lines(&contents);
+    lines.insert(last, format!("{entry}{eol}"));
+    lines.concat()
+}
+
+/// Makes sure the contents have a block for `date`, adding the date header
+/// the same way `insert_entry` would when it's missing. Returns the new
+/// contents and the 1 based number of the block's last date, marker or
+/// entry line, where the next entry for the day goes
+#[must_use]
+pub fn insert_date(contents: &str, date: NaiveDate) -> (String, usize) {
+    let (mut lines, eol) = split_lines(contents);
+    let header = format!("{}{eol}", date.format("%Y-%m-%d"));
+
+    let dates: Vec<_> = lines
+        .iter()
+        .enumerate()
+        .filter_map(
+            |(i, line)| match classify_line(line.trim_end_matches(['\n', '\r'])) {
+                Ok(Line::Date(d)) => Some((i, d)),
+                _ => None,
+            },
+        )
+        .collect();
+
+    // When a date shows up more than once the last block is used, that's
+    // where someone appending by hand would have put it
+    let last = if let Some(pos) = dates.iter().rposition(|&(_, d)| d == date) {
+        let header = dates[pos].0;
+        let block_end = dates.get(pos + 1).map_or(lines.len(), |&(i, _)| i);
+        // Comments at the end of a block usually introduce the next one
+        (header..block_end)
+            .rev()
+            .find(|&i| !lines[i].trim().is_empty() && !is_comment(&lines[i]))
+            .unwrap_or(header)
+    } else if let Some(&(mut next, _)) = dates.iter().find(|&&(_, d)| d > date) {
+        // Keep comments right above the later date attached to it
+        while next > 0 && is_comment(&lines[next - 1]) {
+            next -= 1;
+        }
+        lines.splice(next..next, vec![header, eol.to_string()]);
+        next
+    } else {
+        if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+            lines.push(eol.to_string());
+        }
+        lines.push(header);
+        lines.len() - 1
+    };
+
+    (lines.concat(), last + 1)
+}
+
+/// The lines of the contents with their line endings, the last one given
+/// one when it has none, and the ending for new lines: `\r\n` when the
+/// first line has it, `\n` otherwise
+fn split_lines(contents: &str) -> (Vec<String>, &'static str) {
+    let eol = if contents
+        .lines()
+        .next()
+        .is_some_and(|first| contents[first.len()..].starts_with("\r\n"))
+    {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut lines: Vec<String> = contents.split_inclusive('\n').map(str::to_string).collect();
+    if let Some(last) = lines.last_mut().filter(|l| !l.ends_with('\n')) {
+        last.push_str(eol);
+    }
+    (lines, eol)
+}
+
+/// Fills in `end` on the most recent open entry, the one with the latest
+/// date and start time, and returns the new contents with the closed
+/// entry. None if no entry is open
+#[must_use]
+pub fn close_open_entry(contents: &str, end: NaiveTime) -> Option<(String, TimeEntry)> {
+    let (index, entry) = latest_open_entry(contents)?;
+
+    let closed = TimeEntry {
+        end: Some(end),
+        ..entry
+    };
+    let line = contents.lines().nth(index)?;
+    let line = line.replacen(OPEN_END, &end.format("%H:%M").to_string(), 1);
+    Some((replace_line(contents, index, &line), closed))
+}
+
+/// Removes the entry `close_open_entry` would close, Ex. a timer started
+/// by mistake, and returns the new contents with the removed entry. A date
+/// left with nothing under it goes too. None if no entry is open
+#[must_use]
+pub fn remove_open_entry(contents: &str) -> Option<(String, TimeEntry)> {
+    let (index, entry) = latest_open_entry(contents)?;
+    let lines: Vec<&str> = contents.split_inclusive('\n').collect();
+    let is_date = |i: usize| {
+        let line = lines[i].trim_end_matches(['\n', '\r']);
+        matches!(classify_line(line), Ok(Line::Date(_)))
+    };
+    let is_blank = |i: usize| lines[i].trim().is_empty();
+
+    let mut remove = index..index + 1;
+    if let Some(header) = (0..index).rev().find(|&i| is_date(i)) {
+        let end = (index + 1..lines.len())
+            .find(|&i| is_date(i))
+            .unwrap_or(lines.len());
+        if (header + 1..end).all(|i| i == index || is_blank(i)) {
+            // Take one of the blank lines around the block with it so the
+            // dates around it stay separated by one
+            remove = if header > 0 && is_blank(header - 1) {
+                header - 1..index + 1
+            } else {
+                header..end
+            };
+        }
+    }
+
+    let out = lines
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !remove.contains(i))
+        .map(|(_, line)| *line)
+        .collect();
+    Some((out, entry))
+}
+
+/// Removes entries that repeat an earlier entry of their date exactly or,
+/// with `near`, as `TimeEntry::is_near_duplicate` has it. Returns the new
+/// contents and the removed entries in file order, other lines are kept
+#[must_use]
+pub fn remove_duplicate_entries(contents: &str, near: bool) -> (String, Vec<TimeEntry>) {
+    let mut kept: Vec<TimeEntry> = Vec::new();
+    let mut removed = Vec::new();
+    let mut lines = Vec::new();
+    for (i, e) in entries(contents) {
+        let repeats = |k: &TimeEntry| *k == e || (near && k.is_near_duplicate(&e));
+        if kept.iter().any(repeats) {
+            lines.push(i);
+            removed.push(e);
+        } else {
+            kept.push(e);
+        }
+    }
+
+    let out = contents
+        .split_inclusive('\n')
+        .enumerate()
+        .filter(|(i, _)| !lines.contains(i))
+        .map(|(_, line)| line)
+        .collect();
+    (out, removed)
+}
+
+/// Rewrites the entry with the latest date and start time, cancelled or
+/// not and the last in the file on a tie, as `amend` returns it. Returns
+/// the new contents with the amended entry, which keeps its date. None if
+/// there are no entries
+///
+/// # Errors
+/// Errors if the amended entry is invalid, see `Time::add_entry`
+pub fn amend_latest_entry<F: FnOnce(TimeEntry) -> TimeEntry>(
+    contents: &str,
+    amend: F,
+) -> Result<Option<(String, TimeEntry)>, ParseError> {
+    latest(entries(contents))
+        .map(|found| amend_line(contents, found, amend))
+        .transpose()
+}
+
+/// Like `amend_latest_entry` for the entry `close_open_entry` would close
+///
+/// # Errors
+/// Errors if the amended entry is invalid, see `Time::add_entry`
+pub fn amend_open_entry<F: FnOnce(TimeEntry) -> TimeEntry>(
+    contents: &str,
+    amend: F,
+) -> Result<Option<(String, TimeEntry)>, ParseError> {
+    latest_open_entry(contents)
+        .map(|found| amend_line(contents, found, amend))
+        .transpose()
+}
+
+fn amend_line<F: FnOnce(TimeEntry) -> TimeEntry>(
+    contents: &str,
+    (index, entry): (usize, TimeEntry),
+    amend: F,
+) -> Result<(String, TimeEntry), ParseError> {
+    let amended = TimeEntry {
+        date: entry.date,
+        ..amend(entry)
+    };
+    check_entry(&amended)?;
+    Ok((replace_line(contents, index, &amended.to_string()), amended))
+}
+
+/// The open entry with the latest date and start time, the last in the
+/// file on a tie, with the index of its line. Cancelled entries aren't
+/// running even without an end
+fn latest_open_entry(contents: &str) -> Option<(usize, TimeEntry)> {
+    latest(
+        entries(contents)
+            .into_iter()
+            .filter(|(_, e)| e.is_open() && !e.cancelled),
+    )
+}
+
+fn latest<I: IntoIterator<Item = (usize, TimeEntry)>>(entries: I) -> Option<(usize, TimeEntry)> {
+    entries
+        .into_iter()
+        .max_by_key(|(i, e)| (e.date, e.start, *i))
+}
+
+/// Every entry under a date with the index of its line
+fn entries(contents: &str) -> Vec<(usize, TimeEntry)> {
+    let mut date = None;
+    let mut entries = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        match classify_line(line) {
+            Ok(Line::Date(d)) => date = Some(d),
+            Ok(Line::Entry(e)) => {
+                if let Some(d) = date {
+                    entries.push((i, e.into_entry(d)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    entries
+}
+
+/// Swaps line `index` for `new_line`, keeping every other line and the
+/// line endings as they were
+fn replace_line(contents: &str, index: usize, new_line: &str) -> String {
+    let mut out = String::with_capacity(contents.len());
+    for (i, line) in contents.split_inclusive('\n').enumerate() {
+        if i == index {
+            out.push_str(new_line);
+            if line.ends_with("\r\n") {
+                out.push_str("\r\n");
+            } else if line.ends_with('\n') {
+                out.push('\n');
+            }
+        } else {
+            out.push_str(line);
+        }
+    }
+
+    out
+}
+
+/// Splits the days for which `take` returns true out of the contents,
+/// each with everything under its date and the comments right above it.
+/// Returns the contents left behind and the days taken, in file order and
+/// separated by blank lines. Lines before the first date always stay and
+/// line endings are kept
+#[must_use]
+pub fn take_days<F: FnMut(NaiveDate) -> bool>(contents: &str, mut take: F) -> (String, String) {
+    let (lines, eol) = split_lines(contents);
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let mut headers: Vec<(usize, NaiveDate)> = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if let Ok(Line::Date(date)) = classify_line(line.trim_end_matches(['\n', '\r'])) {
+            // Comments at the top of the file stay with it rather than
+            // going with the first day
+            let mut start = i;
+            if let Some(&(previous, _)) = headers.last() {
+                while start > previous + 1 && is_comment(lines[start - 1]) {
+                    start -= 1;
+                }
+            }
+            headers.push((start, date));
+        }
+    }
+
+    let mut kept: Vec<&str> = lines[..headers.first().map_or(lines.len(), |&(s, _)| s)].to_vec();
+    let mut taken: Vec<&str> = Vec::new();
+    for (n, &(start, date)) in headers.iter().enumerate() {
+        let end = headers.get(n + 1).map_or(lines.len(), |&(s, _)| s);
+        let mut block = &lines[start..end];
+        if take(date) {
+            while let [rest @ .., last] = block {
+                if !last.trim().is_empty() {
+                    break;
+                }
+                block = rest;
+            }
+            if !taken.is_empty() {
+                taken.push(eol);
+            }
+            taken.extend(block);
+        } else {
+            kept.extend(block);
+        }
+    }
+    (kept.concat(), taken.concat())
+}
+
+/// Rewrites the contents of a time.txt file in canonical form. Dates are
+/// sorted and a date written more than once becomes one block, entries
+/// are sorted by start time within their day, times are zero padded and
+/// blank lines only separate dates. Unlike `to_time_txt`
+/// comments are kept, moving along with the date or entry right below them
+///
+/// # Errors
+/// Errors with the first line `parse_time` would reject, a file has to
+/// parse before it can be formatted
+pub fn format_time_txt(contents: &str) -> Result<String, ParseError> {
+    arrange(contents, true)
+}
+
+/// Puts dates in order and entries in order of start time within their
+/// day like `format_time_txt`, but leaves the lines themselves as they are
+/// written. Comments move along with the date or entry right below them
+///
+/// # Errors
+/// Errors with the first line `parse_time` would reject
+pub fn sort_time_txt(contents: &str) -> Result<String, ParseError> {
+    arrange(contents, false)
+}
+
+/// Sorts the blocks and their entries, joining blocks of the same date
+/// under the first one's header. Rewrites dates, markers and entries in
+/// canonical form when `canonical` is set
+fn arrange(contents: &str, canonical: bool) -> Result<String, ParseError> {
+    let mut preamble = Vec::new();
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut pending: Vec<String> = Vec::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        let parsed = classify_line(line).map_err(|e| e.at_line(i + 1))?;
+        let missing_date = || ParseError::MissingDateHeader { line: Some(i + 1) };
+
+        match parsed {
+            Line::Ignored | Line::Skipped if line.trim().is_empty() => {
+                // Comments followed by a blank line belong to what's above
+                let comments = std::mem::take(&mut pending);
+                match blocks.last_mut() {
+                    Some(block) => block.trailing.extend(comments),
+                    None => preamble.extend(comments),
+                }
+            }
+            Line::Ignored | Line::Skipped => pending.push(line.trim_end().to_string()),
+            Line::Date(date) => {
+                let header = if canonical {
+                    date.format("%Y-%m-%d").to_string()
+                } else {
+                    line.trim_end().to_string()
+                };
+                blocks.push(Block {
+                    date,
+                    header,
+                    head: std::mem::take(&mut pending),
+                    markers: Vec::new(),
+                    entries: Vec::new(),
+                    trailing: Vec::new(),
+                });
+            }
+            Line::Marker(marker) => {
+                let block = blocks.last_mut().ok_or_else(missing_date)?;
+                let mut item = std::mem::take(&mut pending);
+                item.push(if canonical {
+                    marker.to_string()
+                } else {
+                    line.trim_end().to_string()
+                });
+                block.markers.push(item);
+            }
+            Line::Entry(e) => {
+                let block = blocks.last_mut().ok_or_else(missing_date)?;
+                let mut e = e.into_entry(block.date);
+                let mut item = std::mem::take(&mut pending);
+                if canonical {
+                    e.description = e
+                        .description
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    item.push(e.to_string().trim_end().to_string());
+                } else {
+                    item.push(line.trim_end().to_string());
+                }
+                block.entries.push((e.start, item));
+            }
+        }
+    }
+    match blocks.last_mut() {
+        Some(block) => block.trailing.append(&mut pending),
+        None => preamble.append(&mut pending),
+    }
+
+    let mut sections: Vec<Vec<String>> = Vec::new();
+    if !preamble.is_empty() {
+        sections.push(preamble);
+    }
+    for mut block in merge_blocks(blocks) {
+        block.entries.sort_by_key(|(start, _)| *start);
+
+        let mut lines = block.head;
+        lines.push(block.header);
+        lines.extend(block.markers.into_iter().flatten());
+        lines.extend(block.entries.into_iter().flat_map(|(_, item)| item));
+        lines.extend(block.trailing);
+        sections.push(lines);
+    }
+
+    let mut out = String::new();
+    for (i, section) in sections.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        for line in section {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+/// Blocks in date order, those of the same date joined under the first
+/// one's header like `parse_time` reads them
+fn merge_blocks(mut blocks: Vec<Block>) -> Vec<Block> {
+    blocks.sort_by_key(|b| b.date);
+    let mut merged: Vec<Block> = Vec::new();
+    for mut block in blocks {
+        let Some(first) = merged.last_mut().filter(|first| first.date == block.date) else {
+            merged.push(block);
+            continue;
+        };
+        // Comments above the repeated header go with what was under it
+        let head = std::mem::take(&mut block.head);
+        match (block.markers.first_mut(), block.entries.first_mut()) {
+            (Some(marker), _) => drop(marker.splice(0..0, head)),
+            (None, Some((_, entry))) => drop(entry.splice(0..0, head)),
+            (None, None) => first.trailing.extend(head),
+        }
+        first.markers.append(&mut block.markers);
+        first.entries.append(&mut block.entries);
+        first.trailing.append(&mut block.trailing);
+    }
+
+    merged
+}
+
+/// A date header and everything up to the next one, each marker and
+/// entry with the comments right above it
+struct Block {
+    date: NaiveDate,
+    header: String,
+    head: Vec<String>,
+    markers: Vec<Vec<String>>,
+    entries: Vec<(NaiveTime, Vec<String>)>,
+    trailing: Vec<String>,
+}
+
+fn is_comment(line: &str) -> bool {
+    line.starts_with("//")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn test_insert_entry() -> Result<(), Box<dyn Error>> {
+        let input = "// My time\n\
+        1822-01-15\n\
+        3:00 4:00 Sketched ideas\n\
+        \n\
+        // Lunch\n\
+        1822-01-17\n\
+        15:30 17:30 Decided on the name\n";
+
+        let existing: TimeEntry = "1822-01-15 5:00 6:00 Built a model".parse()?;
+        assert_eq!(
+            insert_entry(input, &existing),
+            "// My time\n\
+            1822-01-15\n\
+            3:00 4:00 Sketched ideas\n\
+            05:00 06:00 Built a model\n\
+            \n\
+            // Lunch\n\
+            1822-01-17\n\
+            15:30 17:30 Decided on the name\n"
+        );
+
+        let between: TimeEntry = "1822-01-16 9:00 10:00 Rested".parse()?;
+        assert!(insert_entry(input, &between)
+            .contains("ideas\n\n1822-01-16\n09:00 10:00 Rested\n\n// Lunch\n1822-01-17\n"));
+
+        let last: TimeEntry = "1822-01-18 9:00 10:00 Rested".parse()?;
+        assert!(insert_entry(input, &last)
+            .ends_with("17:30 Decided on the name\n\n1822-01-18\n09:00 10:00 Rested\n"));
+
+        assert_eq!(insert_entry("", &last), "1822-01-18\n09:00 10:00 Rested\n");
+
+        let today = NaiveDate::from_ymd_opt(1822, 1, 15).unwrap();
+        assert_eq!(insert_date(input, today), (input.to_string(), 3));
+        let (out, line) = insert_date(input, last.date);
+        assert_eq!(out.lines().nth(line - 1), Some("1822-01-18"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_entry_crlf() -> Result<(), Box<dyn Error>> {
+        let input = "1822-01-15\r\n3:00 4:00 Sketched ideas\r\n\r\n1822-01-17\r\n";
+
+        let existing: TimeEntry = "1822-01-15 5:00 6:00 Built a model".parse()?;
+        assert_eq!(
+            insert_entry(input, &existing),
+            "1822-01-15\r\n\
+            3:00 4:00 Sketched ideas\r\n\
+            05:00 06:00 Built a model\r\n\
+            \r\n\
+            1822-01-17\r\n"
+        );
+
+        let between: TimeEntry = "1822-01-16 9:00 10:00 Rested".parse()?;
+        assert_eq!(
+            insert_entry(input, &between),
+            "1822-01-15\r\n\
+            3:00 4:00 Sketched ideas\r\n\
+            \r\n\
+            1822-01-16\r\n\
+            09:00 10:00 Rested\r\n\
+            \r\n\
+            1822-01-17\r\n"
+        );
+
+        // A last line without an ending gets the file's
+        let last: TimeEntry = "1822-01-18 9:00 10:00 Rested".parse()?;
+        assert_eq!(
+            insert_entry("1822-01-17\r\n9:00 10:00 Rested", &last),
+            "1822-01-17\r\n\
+            9:00 10:00 Rested\r\n\
+            \r\n\
+            1822-01-18\r\n\
+            09:00 10:00 Rested\r\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_close_open_entry() {
+        let input = "1822-01-15\n\
+        3:00 --:-- Forgot to stop\n\
+        1822-01-16\n\
+        9:00 --:-- Sketched ideas #design\n\
+        // Still going\n";
+        let end = NaiveTime::from_hms_opt(10, 15, 0).unwrap();
+
+        let (out, closed) = close_open_entry(input, end).unwrap();
+        assert_eq!(closed.to_string(), "09:00 10:15 Sketched ideas #design");
+        assert_eq!(
+            out,
+            "1822-01-15\n\
+            3:00 --:-- Forgot to stop\n\
+            1822-01-16\n\
+            9:00 10:15 Sketched ideas #design\n\
+            // Still going\n"
+        );
+
+        let (out, _) = close_open_entry(&out, end).unwrap();
+        assert!(close_open_entry(&out, end).is_none());
+    }
+
+    #[test]
+    fn test_open_entry_skips_cancelled() -> Result<(), ParseError> {
+        let input = "1822-01-15\n\
+        9:00 --:-- Still going\n\
+        x 10:00 --:-- Cancelled\n";
+        let end = NaiveTime::from_hms_opt(11, 0, 0).unwrap();
+
+        let (out, closed) = close_open_entry(input, end).unwrap();
+        assert_eq!(closed.description, "Still going");
+        assert_eq!(
+            out,
+            "1822-01-15\n9:00 11:00 Still going\nx 10:00 --:-- Cancelled\n"
+        );
+        assert!(close_open_entry(&out, end).is_none());
+
+        let (out, removed) = remove_open_entry(input).unwrap();
+        assert_eq!(removed.description, "Still going");
+        assert_eq!(out, "1822-01-15\nx 10:00 --:-- Cancelled\n");
+
+        let (_, amended) = amend_open_entry(input, |mut e| {
+            e.append_description("on paper");
+            e
+        })?
+        .unwrap();
+        assert_eq!(amended.description, "Still going on paper");
+        Ok(())
+    }
+
+    #[test]
+    fn test_amend_latest_entry() -> Result<(), ParseError> {
+        let input = "1822-01-16\n\
+        9:00 10:00 Sketched ideas\n\
+        1822-01-15\n\
+        3:00 4:00 Wrote lettres // typo\n";
+
+        let (out, amended) = amend_latest_entry(input, |e| TimeEntry {
+            description: "Sketched ideas #design".to_string(),
+            ..e
+        })?
+        .unwrap();
+        assert_eq!(amended.to_string(), "09:00 10:00 Sketched ideas #design");
+        assert_eq!(
+            out,
+            "1822-01-16\n\
+            09:00 10:00 Sketched ideas #design\n\
+            1822-01-15\n\
+            3:00 4:00 Wrote lettres // typo\n"
+        );
+
+        let same_end = |e: TimeEntry| TimeEntry {
+            end: Some(e.start),
+            ..e
+        };
+        assert!(amend_latest_entry(input, same_end).is_err());
+        assert_eq!(amend_latest_entry("1822-01-16\n", |e| e)?, None);
+
+        let input = "1822-01-15\n9:00 --:-- Sketched ideas\n10:00 11:00 Later\n";
+        let (out, _) = amend_open_entry(input, |mut e| {
+            e.append_description("on paper");
+            e
+        })?
+        .unwrap();
+        assert_eq!(
+            out,
+            "1822-01-15\n09:00 --:-- Sketched ideas on paper\n10:00 11:00 Later\n"
+        );
+        assert_eq!(
+            amend_open_entry("1822-01-15\n10:00 11:00 Later\n", |e| e)?,
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_duplicate_entries() {
+        let input = "1822-01-15\n\
+        3:00 4:00 Sketched ideas\n\
+        // Imported again\n\
+        3:00 4:00 Sketched ideas\n\
+        3:00 4:00 sketched  Ideas.\n\
+        1822-01-16\n\
+        3:00 4:00 Sketched ideas\n";
+
+        let (out, removed) = remove_duplicate_entries(input, false);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(
+            out,
+            "1822-01-15\n\
+            3:00 4:00 Sketched ideas\n\
+            // Imported again\n\
+            3:00 4:00 sketched  Ideas.\n\
+            1822-01-16\n\
+            3:00 4:00 Sketched ideas\n"
+        );
+
+        let (out, removed) = remove_duplicate_entries(&out, true);
+        assert_eq!(removed[0].description, "sketched  Ideas.");
+        assert_eq!(remove_duplicate_entries(&out, true).1, vec![]);
+    }
+
+    #[test]
+    fn test_remove_open_entry() {
+        let input = "1822-01-15\n\
+        3:00 4:00 Sketched ideas\n\
+        9:00 --:-- Started by mistake\n\
+        \n\
+        1822-01-16\n\
+        9:00 --:-- Also by mistake\n";
+
+        let (out, removed) = remove_open_entry(input).unwrap();
+        assert_eq!(removed.description, "Also by mistake");
+        assert_eq!(
+            out,
+            "1822-01-15\n\
+            3:00 4:00 Sketched ideas\n\
+            9:00 --:-- Started by mistake\n"
+        );
+
+        let (out, _) = remove_open_entry(&out).unwrap();
+        assert_eq!(out, "1822-01-15\n3:00 4:00 Sketched ideas\n");
+        assert!(remove_open_entry(&out).is_none());
+    }
+
+    #[test]
+    fn test_take_days() {
+        let input = "// My time\n\
+        1822-01-15\n\
+        3:00 4:00 Sketched ideas\n\
+        \n\
+        // Lunch\n\
+        1822-01-16\n\
+        9:00 10:00 Rested\n\
+        \n\
+        1822-01-17\n\
+        15:30 17:30 Decided on the name\n";
+
+        let cutoff = NaiveDate::from_ymd_opt(1822, 1, 17).unwrap();
+        let (kept, taken) = take_days(input, |d| d < cutoff);
+        assert_eq!(
+            kept,
+            "// My time\n1822-01-17\n15:30 17:30 Decided on the name\n"
+        );
+        assert_eq!(
+            taken,
+            "1822-01-15\n\
+            3:00 4:00 Sketched ideas\n\
+            \n\
+            // Lunch\n\
+            1822-01-16\n\
+            9:00 10:00 Rested\n"
+        );
+
+        assert_eq!(
+            take_days(input, |_| false),
+            (input.to_string(), String::new())
+        );
+        let crlf = input.replace('\n', "\r\n");
+        let (kept, taken) = take_days(&crlf, |d| d < cutoff);
+        assert_eq!(
+            kept,
+            "// My time\r\n1822-01-17\r\n15:30 17:30 Decided on the name\r\n"
+        );
+        assert!(taken
+            .ends_with("Sketched ideas\r\n\r\n// Lunch\r\n1822-01-16\r\n9:00 10:00 Rested\r\n"));
+    }
+
+    #[test]
+    fn test_format_time_txt() -> Result<(), Box<dyn Error>> {
+        let input = "// My time\n\
+        \n\
+        1822-01-17\n\
+        15:30 17:30   Decided on the name  \n\
+        \n\
+        \n\
+        // Before the 15th\n\
+        1822-01-15\n\
+        // Afternoon\n\
+        5:00 6:00 Built a model\n\
+        3:00 4:00 Sketched ideas\n\
+        SICK half\n\
+        // End of the 15th\n";
+
+        let formatted = format_time_txt(input)?;
+        assert_eq!(
+            formatted,
+            "// My time\n\
+            \n\
+            // Before the 15th\n\
+            1822-01-15\n\
+            SICK half\n\
+            03:00 04:00 Sketched ideas\n\
+            // Afternoon\n\
+            05:00 06:00 Built a model\n\
+            // End of the 15th\n\
+            \n\
+            1822-01-17\n\
+            15:30 17:30 Decided on the name\n"
+        );
+        assert_eq!(format_time_txt(&formatted)?, formatted);
+
+        assert_eq!(
+            format_time_txt("9:00 10:00 No date\n"),
+            Err(ParseError::MissingDateHeader { line: Some(1) })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_time_txt_repeated_date() -> Result<(), ParseError> {
+        let input = "1822-01-15\n\
+        5:00 6:00 Built a model\n\
+        \n\
+        1822-01-16\n\
+        9:00 10:00 Rested\n\
+        \n\
+        // Forgot this one\n\
+        1822-01-15\n\
+        3:00 4:00 Sketched ideas\n";
+
+        let formatted = format_time_txt(input)?;
+        assert_eq!(
+            formatted,
+            "1822-01-15\n\
+            // Forgot this one\n\
+            03:00 04:00 Sketched ideas\n\
+            05:00 06:00 Built a model\n\
+            \n\
+            1822-01-16\n\
+            09:00 10:00 Rested\n"
+        );
+        // The same as writing the parsed file, apart from the comment
+        assert_eq!(
+            format_time_txt(&input.replace("// Forgot this one\n", ""))?,
+            crate::parse_time(input)?.to_time_txt()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_time_txt() -> Result<(), ParseError> {
+        let input = "1822-01-17\n\
+        15:30 17:30   Decided on the name\n\
+        \n\
+        // Before the 15th\n\
+        1822-01-15\n\
+        5:00 6:00 Built a model\n\
+        // Morning\n\
+        3:00 4:00 Sketched ideas\n";
+
+        assert_eq!(
+            sort_time_txt(input)?,
+            "// Before the 15th\n\
+            1822-01-15\n\
+            // Morning\n\
+            3:00 4:00 Sketched ideas\n\
+            5:00 6:00 Built a model\n\
+            \n\
+            1822-01-17\n\
+            15:30 17:30   Decided on the name\n"
+        );
+        Ok(())
+    }
+}